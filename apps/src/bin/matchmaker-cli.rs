@@ -0,0 +1,103 @@
+//! A thin client for a running matchmaker's admin RPC socket. Connects,
+//! sends a single framed [`AdminRequest`], prints the decoded
+//! [`AdminResponse`] and exits.
+
+use std::net::SocketAddr;
+
+use anoma_apps::node::matchmaker::{self, AdminRequest, EngineId};
+use clap::{Parser, Subcommand};
+
+/// Inspect and steer a running matchmaker over its admin RPC socket.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Address of the matchmaker's admin RPC socket.
+    #[clap(long)]
+    admin_addr: SocketAddr,
+    /// Shared secret the matchmaker's admin RPC socket was configured
+    /// with.
+    #[clap(long)]
+    shared_secret: String,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List the IDs of intents the matchmaker currently holds.
+    ListPendingIntents,
+    /// Fetch a few counters about the matchmaker's current state.
+    Stats,
+    /// Inject an intent as if it had arrived from the gossiper.
+    InjectIntent {
+        /// Path to a file holding the Borsh-encoded intent data.
+        data_path: String,
+    },
+    /// Evict an intent from the matchmaker's local bookkeeping.
+    DropIntent {
+        /// Hex-encoded ID of the intent to drop.
+        id: String,
+    },
+    /// List the IDs of the engines currently hosted by the matchmaker.
+    ListEngines,
+    /// Start a new engine from its dylib and register it under `id`,
+    /// replacing any existing engine with the same ID.
+    AddEngine {
+        /// Identifies the new engine among the others hosted by the
+        /// matchmaker.
+        id: String,
+        /// Path of the engine's dylib, relative to the Anoma binary
+        /// directory.
+        matchmaker_path: String,
+    },
+    /// Stop forwarding intents to an engine, without disturbing the
+    /// others.
+    RemoveEngine {
+        /// The ID of the engine to remove.
+        id: String,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let request = match cli.command {
+        Command::ListPendingIntents => AdminRequest::ListPendingIntents,
+        Command::Stats => AdminRequest::Stats,
+        Command::InjectIntent { data_path } => {
+            let data = std::fs::read(&data_path).unwrap_or_else(|err| {
+                eprintln!("Failed to read {}: {}", data_path, err);
+                std::process::exit(1);
+            });
+            AdminRequest::InjectIntent { data }
+        }
+        Command::DropIntent { id } => {
+            let id = hex::decode(&id).unwrap_or_else(|err| {
+                eprintln!("Invalid hex intent ID {}: {}", id, err);
+                std::process::exit(1);
+            });
+            AdminRequest::DropIntent { id }
+        }
+        Command::ListEngines => AdminRequest::ListEngines,
+        Command::AddEngine { id, matchmaker_path } => {
+            AdminRequest::AddEngine {
+                id: EngineId(id),
+                matchmaker_path,
+            }
+        }
+        Command::RemoveEngine { id } => {
+            AdminRequest::RemoveEngine { id: EngineId(id) }
+        }
+    };
+
+    match matchmaker::send_request(cli.admin_addr, &cli.shared_secret, &request)
+    {
+        Ok(response) => println!("{:#?}", response),
+        Err(err) => {
+            eprintln!(
+                "Failed to reach matchmaker admin RPC at {}: {}",
+                cli.admin_addr, err
+            );
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Crate root for the Anoma node and client application library. The
+//! `matchmaker-cli` binary is the only consumer built out of this
+//! repository snapshot, but the module layout mirrors the full Anoma
+//! workspace so the rest of it can be dropped in without reshuffling
+//! anything here.
+
+#[path = "lib/cli.rs"]
+pub mod cli;
+#[path = "lib/client.rs"]
+pub mod client;
+#[path = "lib/config.rs"]
+pub mod config;
+#[path = "lib/node.rs"]
+pub mod node;
+#[path = "lib/wasm_loader.rs"]
+pub mod wasm_loader;
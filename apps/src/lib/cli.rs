@@ -0,0 +1,10 @@
+//! Minimal CLI plumbing shared by the node and client binaries.
+
+pub mod args;
+
+/// Print nothing and exit the process with `code`. A thin wrapper so
+/// call sites read as an intentional, controlled exit rather than a
+/// bare `std::process::exit`.
+pub fn safe_exit(code: i32) -> ! {
+    std::process::exit(code)
+}
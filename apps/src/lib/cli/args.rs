@@ -0,0 +1,13 @@
+//! Arguments shared by the client-side RPC calls in [`crate::client`].
+
+#[cfg(not(feature = "ABCI"))]
+use tendermint_config::net::Address as TendermintAddress;
+#[cfg(feature = "ABCI")]
+use tendermint_config_abci::net::Address as TendermintAddress;
+
+/// Arguments for a read-only query against the ledger.
+#[derive(Debug, Clone)]
+pub struct Query {
+    /// The ledger address to query.
+    pub ledger_address: TendermintAddress,
+}
@@ -0,0 +1,88 @@
+//! Thin ledger clients used by the node-side matchmaker (and, in the
+//! full Anoma workspace, the `anomac` client binary): read-only queries
+//! in [`rpc`] and transaction submission in [`tx`].
+//!
+//! Both talk to the ledger's Tendermint JSON-RPC endpoint over the
+//! connection opened by [`connect`], so the matchmaker's SOCKS5 proxy
+//! (already used for its gossiper connection) covers the ledger
+//! connection too, instead of depending on a separate HTTP
+//! client's own, unproxied networking.
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use serde::de::DeserializeOwned;
+#[cfg(not(feature = "ABCI"))]
+use tendermint_config::net::Address as TendermintAddress;
+#[cfg(feature = "ABCI")]
+use tendermint_config_abci::net::Address as TendermintAddress;
+
+use crate::node::matchmaker::socks5;
+
+pub mod rpc;
+pub mod tx;
+
+/// Open a connection to `ledger_address`, through `proxy` if one is
+/// given, the same way [`crate::node::matchmaker::supervisor`] routes
+/// its own outbound connections.
+fn connect(
+    ledger_address: &TendermintAddress,
+    proxy: Option<SocketAddr>,
+) -> io::Result<TcpStream> {
+    let (host, port) = match ledger_address {
+        TendermintAddress::Tcp { host, port, .. } => (host.clone(), *port),
+        TendermintAddress::Unix { .. } => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a unix socket ledger address can't be routed through a \
+                 SOCKS5 proxy",
+            ))
+        }
+    };
+    match proxy {
+        Some(proxy_addr) => socks5::connect(proxy_addr, &host, port),
+        None => TcpStream::connect((host.as_str(), port)),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct JsonRpcResponse<T> {
+    result: T,
+}
+
+/// Send a Tendermint JSON-RPC request for `method` with `params` to
+/// `ledger_address`, through `proxy` if one is given, and decode the
+/// `result` field of the response.
+pub(super) fn jsonrpc<T: DeserializeOwned>(
+    ledger_address: &TendermintAddress,
+    proxy: Option<SocketAddr>,
+    method: &str,
+    params: serde_json::Value,
+) -> io::Result<T> {
+    let mut stream = connect(ledger_address, proxy)?;
+
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": method,
+        "params": params,
+    })
+    .to_string();
+    let request = format!(
+        "POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        ledger_address,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes())?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let body_start = response.find("\r\n\r\n").map(|i| i + 4).unwrap_or(0);
+    let response: JsonRpcResponse<T> = serde_json::from_str(
+        &response[body_start..],
+    )
+    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(response.result)
+}
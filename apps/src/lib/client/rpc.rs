@@ -0,0 +1,43 @@
+//! Read-only RPC queries against the ledger.
+
+use std::io;
+use std::net::SocketAddr;
+
+use anoma::types::storage::Epoch;
+use borsh::BorshDeserialize;
+use serde::Deserialize;
+
+use crate::cli::args;
+
+#[derive(Deserialize)]
+struct AbciQueryResponse {
+    response: AbciQueryResult,
+}
+
+#[derive(Deserialize)]
+struct AbciQueryResult {
+    value: String,
+}
+
+/// Query the ledger's current epoch, through `proxy` if one is given.
+///
+/// Returns an error rather than panicking on a transport or decode
+/// failure, since this is also used as a liveness probe
+/// ([`super::super::node::matchmaker::supervisor::ping_ledger`]): a
+/// genuinely unreachable ledger is the one case that check exists to
+/// detect, not a reason to crash the thread running it.
+pub async fn query_epoch(
+    args: args::Query,
+    proxy: Option<SocketAddr>,
+) -> io::Result<Epoch> {
+    let response: AbciQueryResponse = super::jsonrpc(
+        &args.ledger_address,
+        proxy,
+        "abci_query",
+        serde_json::json!({ "path": "/epoch", "data": "", "prove": false }),
+    )?;
+    let value = hex::decode(&response.response.value)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Epoch::try_from_slice(&value)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
@@ -0,0 +1,44 @@
+//! Transaction submission to the ledger.
+
+use std::net::SocketAddr;
+
+use anoma::types::key::ed25519::Keypair;
+use anoma::types::transaction::WrapperTx;
+use borsh::BorshSerialize;
+use serde::Deserialize;
+#[cfg(not(feature = "ABCI"))]
+use tendermint_config::net::Address as TendermintAddress;
+#[cfg(feature = "ABCI")]
+use tendermint_config_abci::net::Address as TendermintAddress;
+
+/// The ledger's response to a submitted transaction.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TxResponse {
+    /// The ledger's result code for the submission; zero means accepted.
+    pub code: u32,
+    /// A human-readable description of the result.
+    pub log: String,
+    /// The hash the ledger assigned the submitted transaction.
+    pub hash: String,
+}
+
+/// Sign `tx` with `signing_key` and broadcast it to the ledger at
+/// `ledger_address`, through `proxy` if one is given.
+pub async fn broadcast_tx(
+    ledger_address: TendermintAddress,
+    tx: WrapperTx,
+    signing_key: &Keypair,
+    proxy: Option<SocketAddr>,
+) -> std::io::Result<TxResponse> {
+    let signed = tx.sign(signing_key);
+    let tx_bytes = signed.try_to_vec().map_err(|err| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    })?;
+
+    super::jsonrpc(
+        &ledger_address,
+        proxy,
+        "broadcast_tx_sync",
+        serde_json::json!({ "tx": hex::encode(tx_bytes) }),
+    )
+}
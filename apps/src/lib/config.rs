@@ -0,0 +1,37 @@
+//! Configuration for a node's matchmaker and its surrounding plumbing
+//! (the admin RPC, the peer mesh, persistence).
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::node::matchmaker::EngineConfig;
+
+/// Configuration for a node's matchmaker.
+#[derive(Debug, Clone)]
+pub struct Matchmaker {
+    /// The matchmaker engines to host, each running its own dylib.
+    pub engines: Vec<EngineConfig>,
+    /// Path (relative to the wasm directory) of the transaction code used
+    /// to submit matched intents.
+    pub tx_code_path: PathBuf,
+    /// An optional SOCKS5 proxy that outbound connections to the intent
+    /// gossiper and the ledger are routed through. Connections to peer
+    /// matchmakers are dialed directly, not through it.
+    pub proxy: Option<SocketAddr>,
+    /// Address to bind the admin RPC control socket on, if any.
+    pub admin_addr: Option<SocketAddr>,
+    /// Shared secret used to authenticate admin RPC connections.
+    pub admin_shared_secret: String,
+    /// How often the health check supervisor probes the gossiper and
+    /// ledger connections.
+    pub health_check_interval: Duration,
+    /// Where to persist the matchmaker's intent mempool.
+    pub db_path: PathBuf,
+    /// Addresses of the peer matchmakers to maintain mesh connections to.
+    pub peers: Vec<SocketAddr>,
+    /// Address to accept inbound peer mesh connections on, if any.
+    pub mesh_addr: Option<SocketAddr>,
+    /// Shared secret used to authenticate peer mesh connections.
+    pub mesh_shared_secret: String,
+}
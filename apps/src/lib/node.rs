@@ -0,0 +1,5 @@
+//! Node-side components: the intent gossip network and the matchmakers
+//! that consume the intents it relays.
+
+pub mod gossip;
+pub mod matchmaker;
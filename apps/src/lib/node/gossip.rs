@@ -0,0 +1,4 @@
+//! The intent gossip network: nodes relay intents to each other and to any
+//! matchmakers connected to them.
+
+pub mod matchmakers;
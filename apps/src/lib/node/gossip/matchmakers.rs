@@ -0,0 +1,142 @@
+//! A lightweight client for a node's intent gossiper: the matchmaker
+//! dials in as a regular gossip peer, receiving intents as they're
+//! gossiped in and reporting back which ones its engines have matched.
+//! This mirrors the framing used by [`crate::node::matchmaker::mesh`] for
+//! matchmaker-to-matchmaker connections, since both talk length-prefixed
+//! Borsh over TCP. Unlike the mesh's connections, this one is routed
+//! through the SOCKS5 proxy when one is configured.
+
+use std::io;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::node::matchmaker::framing::{read_framed, write_framed};
+use crate::node::matchmaker::socks5;
+
+/// A message sent by the gossiper node to a connected matchmaker.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub enum MsgFromServer {
+    /// A newly gossiped intent for the matchmaker to apply to its
+    /// engines.
+    AddIntent {
+        /// The intent's ID.
+        id: Vec<u8>,
+        /// The Borsh-encoded intent data.
+        data: Vec<u8>,
+    },
+}
+
+/// A message sent by a connected matchmaker to the gossiper node.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub enum MsgFromClient {
+    /// IDs of intents a match has just consumed, so the gossiper stops
+    /// relaying them any further.
+    Matched {
+        /// IDs of the matched intents.
+        intent_ids: Vec<Vec<u8>>,
+    },
+}
+
+/// The matchmaker's read half of its gossiper connection.
+#[derive(Debug)]
+pub struct ClientListener {
+    stream: TcpStream,
+}
+
+/// The matchmaker's write half of its gossiper connection, for reporting
+/// matches back. Cheap to clone; every clone shares the same writer
+/// thread and underlying connection.
+#[derive(Debug, Clone)]
+pub struct ClientDialer {
+    message_send: mpsc::Sender<MsgFromClient>,
+}
+
+impl ClientListener {
+    /// Connect to the gossiper node at `gossiper_addr`, through `proxy`
+    /// if one is given, retrying with a capped exponential backoff
+    /// starting at `initial_backoff` until it succeeds (so callers never
+    /// have to handle a connection failure here), and split the
+    /// connection into a [`ClientListener`] (for receiving gossiped
+    /// intents) and a [`ClientDialer`] (for reporting matches back).
+    pub fn new_pair(
+        gossiper_addr: SocketAddr,
+        proxy: Option<SocketAddr>,
+        initial_backoff: Duration,
+    ) -> (Self, ClientDialer) {
+        let mut backoff = initial_backoff;
+        let stream = loop {
+            let connected = match proxy {
+                Some(proxy_addr) => socks5::connect(
+                    proxy_addr,
+                    &gossiper_addr.ip().to_string(),
+                    gossiper_addr.port(),
+                ),
+                None => TcpStream::connect(gossiper_addr),
+            };
+            match connected {
+                Ok(stream) => break stream,
+                Err(err) => {
+                    tracing::warn!(
+                        "Matchmaker failed to connect to its intent \
+                         gossiper at {}, retrying in {:?}: {}",
+                        gossiper_addr,
+                        backoff,
+                        err
+                    );
+                    std::thread::sleep(backoff);
+                    backoff = (backoff * 2).min(Duration::from_secs(60));
+                }
+            }
+        };
+
+        let write_stream = stream.try_clone().unwrap_or_else(|err| {
+            panic!(
+                "Failed to clone the matchmaker's gossiper connection: {}",
+                err
+            )
+        });
+        let (message_send, message_recv) = mpsc::channel();
+        std::thread::spawn(move || run_writer(write_stream, message_recv));
+
+        (Self { stream }, ClientDialer { message_send })
+    }
+
+    /// Read messages from the gossiper until the connection drops or a
+    /// message fails to decode, calling `on_message` for each one.
+    pub fn listen(
+        &mut self,
+        mut on_message: impl FnMut(MsgFromServer),
+    ) -> io::Result<()> {
+        loop {
+            let message = read_framed(&mut self.stream)?;
+            on_message(message);
+        }
+    }
+}
+
+impl ClientDialer {
+    /// Send a message to the gossiper node. Best-effort: if the
+    /// connection has dropped, the message is silently lost, the same
+    /// way it would be if the gossiper had never received it.
+    pub fn send(&self, message: MsgFromClient) {
+        let _ = self.message_send.send(message);
+    }
+}
+
+fn run_writer(
+    mut stream: TcpStream,
+    message_recv: mpsc::Receiver<MsgFromClient>,
+) {
+    while let Ok(message) = message_recv.recv() {
+        if let Err(err) = write_framed(&mut stream, &message) {
+            tracing::warn!(
+                "Matchmaker's write to its intent gossiper failed: {}",
+                err
+            );
+            return;
+        }
+    }
+}
@@ -1,10 +1,33 @@
+use std::collections::BinaryHeap;
 use std::env;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
+use std::time::Duration;
 
-use anoma::proto::{Intent, Tx};
+mod admin_rpc;
+mod broker;
+// Visible to the rest of the crate (not just this module's descendants)
+// because `node::gossip::matchmakers` also frames its gossiper
+// connection the same way and needs the same allocation cap.
+pub(crate) mod framing;
+mod mesh;
+mod persistence;
+mod retry;
+// Visible to the rest of the crate (not just this module's descendants)
+// because `node::gossip::matchmakers` and `client` also need to route
+// their own connections through the same proxy.
+pub(crate) mod socks5;
+mod supervisor;
+
+pub use admin_rpc::{send_request, AdminRequest, AdminResponse};
+pub use broker::{EngineConfig, EngineId};
+pub use supervisor::{ConnectivityReport, ConnectivityState};
+
+use anoma::proto::Tx;
 use anoma::types::address::{self, Address};
 use anoma::types::dylib;
 use anoma::types::intent::{IntentTransfers, MatchedExchanges};
@@ -36,8 +59,16 @@ use crate::{cli, config, wasm_loader};
 #[tokio::main]
 pub async fn run(
     config::Matchmaker {
-        matchmaker_path,
+        engines,
         tx_code_path,
+        proxy,
+        admin_addr,
+        admin_shared_secret,
+        health_check_interval,
+        db_path,
+        peers,
+        mesh_addr,
+        mesh_shared_secret,
     }: config::Matchmaker,
     intent_gossiper_addr: SocketAddr,
     ledger_addr: TendermintAddress,
@@ -47,12 +78,20 @@ pub async fn run(
 ) {
     let (runner, result_handler) = Runner::new_pair(
         intent_gossiper_addr,
-        matchmaker_path,
+        engines,
         tx_code_path,
         ledger_addr,
         tx_signing_key,
         tx_source_address,
         wasm_dir,
+        proxy,
+        admin_addr,
+        admin_shared_secret,
+        health_check_interval,
+        db_path,
+        peers,
+        mesh_addr,
+        mesh_shared_secret,
     );
 
     // Instantiate and run the matchmaker implementation in a dedicated thread
@@ -69,16 +108,43 @@ pub async fn run(
     }
 }
 
-/// A matchmaker receive intents and tries to find a match with previously
-/// received intent.
+/// A matchmaker receives intents and fans them out to every matchmaker
+/// engine hosted by its [`broker::Broker`], each of which tries to find a
+/// match with previously received intents.
 #[derive(Debug)]
 pub struct Runner {
-    matchmaker_path: PathBuf,
+    /// Engines to instantiate once [`Runner::listen`] starts. Consumed on
+    /// start; engines added afterwards go through the admin RPC instead.
+    engine_configs: Vec<broker::EngineConfig>,
     /// The client listener. This is consumed once the listener is started with
     /// [`Matchmaker::listen`].
     listener: Option<ClientListener>,
-    /// Sender of results of matched intents to the [`ResultHandler`].
-    result_send: tokio::sync::mpsc::UnboundedSender<AddIntentResult>,
+    /// Fans intents out to every hosted engine. Shared with the admin RPC
+    /// server, which can add or remove engines at runtime.
+    broker: broker::Broker,
+    /// Address to bind the admin RPC control socket on, if any.
+    admin_addr: Option<SocketAddr>,
+    /// Shared secret used to authenticate admin RPC connections.
+    admin_shared_secret: String,
+    /// Address of the intent gossiper node, kept around so the connection
+    /// can be re-established if it drops.
+    intent_gossiper_addr: SocketAddr,
+    /// The ledger address, probed periodically by the health check
+    /// supervisor.
+    ledger_address: TendermintAddress,
+    /// An optional SOCKS5 proxy that the gossiper connection (and its
+    /// health check probes) are routed through.
+    proxy: Option<SocketAddr>,
+    /// How often the health check supervisor probes the gossiper and
+    /// ledger connections.
+    health_check_interval: Duration,
+    /// Shares newly seen intents and consumed-intent notifications with
+    /// other matchmakers in the mesh.
+    mesh: mesh::Mesh,
+    /// Addresses of the peer matchmakers to maintain mesh connections to.
+    peers: Vec<SocketAddr>,
+    /// Address to accept inbound peer mesh connections on, if any.
+    mesh_addr: Option<SocketAddr>,
 }
 
 /// Result handler processes the results sent from the matchmaker [`Runner`].
@@ -86,8 +152,12 @@ pub struct Runner {
 pub struct ResultHandler {
     /// A dialer can send messages to the connected intent gossip node
     dialer: ClientDialer,
-    /// A receiver of matched intents results from the [`Runner`].
-    result_recv: tokio::sync::mpsc::UnboundedReceiver<AddIntentResult>,
+    /// A receiver of matched intents results from the [`Runner`]'s engines,
+    /// each tagged with the [`broker::EngineId`] that produced it.
+    result_recv: tokio::sync::mpsc::UnboundedReceiver<(
+        broker::EngineId,
+        AddIntentResult,
+    )>,
     /// The ledger address to send any crafted transaction to
     ledger_address: net::Address,
     /// The code of the transaction that is going to be send to a ledger.
@@ -96,6 +166,17 @@ pub struct ResultHandler {
     tx_source_address: Address,
     /// A keypair that will be used to sign transactions.
     tx_signing_key: Rc<Keypair>,
+    /// An optional SOCKS5 proxy (e.g. a local Tor daemon) that outbound
+    /// connections to the ledger are routed through.
+    proxy: Option<SocketAddr>,
+    /// Broadcasts "consumed" notifications to peer matchmakers once a
+    /// match fires, so they evict those intents from their own mempools
+    /// instead of also trying to submit a transaction for them.
+    mesh: mesh::Mesh,
+    /// Number of transaction submissions currently queued for retry.
+    /// Shared with the [`broker::Broker`] so it can be reported through
+    /// the admin RPC.
+    retrying_submissions: Arc<AtomicU64>,
 }
 
 /// The loaded implementation's dylib and its state
@@ -115,40 +196,232 @@ struct MatchmakerImpl {
 #[derive(Debug)]
 struct MatchmakerState(Arc<*mut c_void>);
 
-/// Matchmaker message for communication between the runner, P2P and the
-/// implementation
+// SAFETY: a `MatchmakerImpl` is only ever created and used inside the
+// single worker thread spawned for it by [`broker::Broker::spawn_engine`]
+// (in [`run_engine`]) and never shared, so it only needs to be `Send`, not
+// `Sync`.
+unsafe impl Send for MatchmakerImpl {}
+
+/// Load a matchmaker engine's dylib and instantiate it.
+///
+/// The dylib should be built in the same directory as where Anoma
+/// binaries are, even when ran via `cargo run`. Anoma's pre-built binaries
+/// are distributed with the dylib(s) in the same directory.
+fn load_dylib(matchmaker_path: &Path) -> MatchmakerImpl {
+    let dylib_dir = {
+        let anoma_path = env::current_exe().unwrap();
+        anoma_path
+            .parent()
+            .map(|path| path.to_owned())
+            .unwrap_or_else(|| ".".into())
+    };
+    let mut matchmaker_dylib = dylib_dir.join(matchmaker_path);
+    matchmaker_dylib.set_extension(dylib::FILE_EXT);
+    tracing::info!(
+        "Running matchmaker from {}",
+        matchmaker_dylib.to_string_lossy()
+    );
+    if !matchmaker_dylib.exists() {
+        panic!(
+            "The matchmaker library couldn't not be found. Did you build \
+             it?"
+        )
+    }
+    let matchmaker_code = unsafe { Library::new(matchmaker_dylib).unwrap() };
+
+    // Instantiate the matchmaker
+    let new_matchmaker: libloading::Symbol<
+        unsafe extern "C" fn() -> *mut c_void,
+    > = unsafe { matchmaker_code.get(b"_new_matchmaker").unwrap() };
+
+    let state = MatchmakerState(Arc::new(unsafe { new_matchmaker() }));
+
+    MatchmakerImpl {
+        state,
+        library: matchmaker_code,
+    }
+}
+
+/// Run one matchmaker engine on a dedicated worker thread: load its dylib,
+/// then apply every [`MatchmakerMessage`] the broker sends on
+/// `message_recv` until the channel closes, which happens once the engine
+/// is removed from the broker and its sender is dropped.
+fn run_engine(
+    id: broker::EngineId,
+    matchmaker_path: PathBuf,
+    message_recv: mpsc::Receiver<MatchmakerMessage>,
+    result_send: tokio::sync::mpsc::UnboundedSender<(
+        broker::EngineId,
+        AddIntentResult,
+    )>,
+    intent_store: Arc<persistence::IntentStore>,
+) {
+    let r#impl = load_dylib(&matchmaker_path);
+
+    while let Ok(message) = message_recv.recv() {
+        match message {
+            MatchmakerMessage::ApplyIntent { id: intent_id, data, ack } => {
+                let result =
+                    apply_intent(&r#impl, &intent_store, &id, intent_id, data);
+                // The broker is only waiting to pace itself against a slow
+                // engine; it doesn't care if it has since stopped waiting.
+                let _ = ack.send(());
+                if result_send.send((id.clone(), result)).is_err() {
+                    // The `ResultHandler` has shut down; nothing left to do.
+                    break;
+                }
+            }
+            MatchmakerMessage::RemoveIntent { id: intent_id } => {
+                remove_intent(&r#impl, &intent_id);
+            }
+        }
+    }
+}
+
+/// Forward an intent into a matchmaker engine's dylib via `_add_intent`.
+/// The intent itself is already persisted by [`broker::Broker::broadcast_intent`]
+/// before any engine sees it; this only records `engine_id`'s consumption
+/// of whatever the match consumed, which evicts an intent from the store
+/// once every engine it was fanned out to has consumed it.
+fn apply_intent(
+    r#impl: &MatchmakerImpl,
+    intent_store: &persistence::IntentStore,
+    engine_id: &broker::EngineId,
+    intent_id: Vec<u8>,
+    intent_data: Vec<u8>,
+) -> AddIntentResult {
+    let add_intent: libloading::Symbol<
+        unsafe extern "C" fn(
+            *mut c_void,
+            &Vec<u8>,
+            &Vec<u8>,
+        ) -> AddIntentResult,
+    > = unsafe { r#impl.library.get(b"_add_intent").unwrap() };
+
+    let result =
+        unsafe { add_intent(*r#impl.state.0, &intent_id, &intent_data) };
+
+    if let Some(matched_ids) = &result.matched_intents {
+        for id in matched_ids {
+            intent_store.consume(id, engine_id);
+        }
+    }
+
+    result
+}
+
+/// Evict an intent from a matchmaker engine's own in-memory match graph
+/// via `_remove_intent`, so a "consumed elsewhere" notification (from the
+/// peer mesh or an operator's admin RPC drop) actually stops the engine
+/// from matching it again, instead of only clearing its RocksDB backup.
+fn remove_intent(r#impl: &MatchmakerImpl, intent_id: &[u8]) {
+    let remove_intent: libloading::Symbol<
+        unsafe extern "C" fn(*mut c_void, &Vec<u8>),
+    > = unsafe { r#impl.library.get(b"_remove_intent").unwrap() };
+
+    unsafe { remove_intent(*r#impl.state.0, &intent_id.to_vec()) };
+}
+
+/// Derive a synthetic intent ID for an intent injected through the admin
+/// RPC, which (unlike intents relayed by the gossiper) doesn't come with
+/// one already assigned.
+fn derive_injected_intent_id(intent_data: &[u8]) -> Vec<u8> {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    intent_data.hash(&mut hasher);
+    hasher.finish().to_be_bytes().to_vec()
+}
+
+/// Matchmaker message for communication between the broker and an engine's
+/// worker thread.
 #[derive(Debug)]
 pub enum MatchmakerMessage {
-    /// Run the matchmaker with the given intent
-    ApplyIntent(Intent, oneshot::Sender<bool>),
+    /// Apply the intent identified by `id` to the engine, acknowledging
+    /// once it has been handed to the dylib.
+    ApplyIntent {
+        /// The intent's ID.
+        id: Vec<u8>,
+        /// The Borsh-encoded intent data.
+        data: Vec<u8>,
+        /// Signalled once the intent has been applied.
+        ack: oneshot::Sender<()>,
+    },
+    /// Evict an intent from the engine's own in-memory match graph, e.g.
+    /// because it was consumed by another engine in the mesh or dropped
+    /// by an operator.
+    RemoveIntent {
+        /// The intent's ID.
+        id: Vec<u8>,
+    },
 }
 
 impl Runner {
     /// Create a new matchmaker and a dialer that can be used to send messages
     /// to the intent gossiper node.
+    ///
+    /// If `proxy` is set, the connection to the intent gossiper is
+    /// established through a SOCKS5 CONNECT handshake against that address
+    /// instead of dialing it directly, so the matchmaker's network location
+    /// stays hidden behind the proxy (e.g. a local Tor daemon).
+    #[allow(clippy::too_many_arguments)]
     pub fn new_pair(
         intent_gossiper_addr: SocketAddr,
-        matchmaker_path: PathBuf,
+        engines: Vec<broker::EngineConfig>,
         tx_code_path: PathBuf,
         ledger_address: TendermintAddress,
         tx_signing_key: Rc<Keypair>,
         tx_source_address: Address,
         wasm_dir: impl AsRef<Path>,
+        proxy: Option<SocketAddr>,
+        admin_addr: Option<SocketAddr>,
+        admin_shared_secret: String,
+        health_check_interval: Duration,
+        db_path: impl AsRef<Path>,
+        peers: Vec<SocketAddr>,
+        mesh_addr: Option<SocketAddr>,
+        mesh_shared_secret: String,
     ) -> (Self, ResultHandler) {
-        // Setup a channel for sending matchmaker results from `Self` to the
-        // `ResultHandler`
+        // Setup a channel for sending matchmaker results from every engine
+        // hosted by the broker to the `ResultHandler`
         let (result_send, result_recv) = tokio::sync::mpsc::unbounded_channel();
 
-        // Prepare a client for intent gossiper node connection
-        let (listener, dialer) = ClientListener::new_pair(intent_gossiper_addr);
+        // Prepare a client for intent gossiper node connection, optionally
+        // routed through a SOCKS5 proxy
+        let (listener, dialer) = ClientListener::new_pair(
+            intent_gossiper_addr,
+            proxy,
+            health_check_interval,
+        );
 
         let tx_code = wasm_loader::read_wasm(&wasm_dir, tx_code_path);
 
+        let intent_store = Arc::new(persistence::IntentStore::open(db_path));
+        let retrying_submissions = Arc::new(AtomicU64::new(0));
+        let broker = broker::Broker::new(
+            result_send,
+            intent_store,
+            supervisor::SharedConnectivity::default(),
+            retrying_submissions.clone(),
+        );
+        let mesh =
+            mesh::Mesh::new(mesh_addr, mesh_shared_secret, broker.clone());
+
         (
             Self {
-                matchmaker_path,
+                engine_configs: engines,
                 listener: Some(listener),
-                result_send,
+                broker,
+                admin_addr,
+                admin_shared_secret,
+                intent_gossiper_addr,
+                ledger_address: ledger_address.clone(),
+                proxy,
+                health_check_interval,
+                mesh: mesh.clone(),
+                peers,
+                mesh_addr,
             },
             ResultHandler {
                 dialer,
@@ -157,80 +430,95 @@ impl Runner {
                 tx_code,
                 tx_source_address,
                 tx_signing_key,
+                proxy,
+                mesh,
+                retrying_submissions,
             },
         )
     }
 
     pub fn listen(mut self) {
-        // Load the implementation's dylib and instantiate it. We have to do
-        // that here instead of `Self::new_pair`, because we cannot send
-        // it across threads and the listener is launched in a dedicated thread.
-
-        // The dylib should be built in the same directory as where Anoma
-        // binaries are, even when ran via `cargo run`. Anoma's pre-built
-        // binaries are distributed with the dylib(s) in the same directory.
-        let dylib_dir = {
-            let anoma_path = env::current_exe().unwrap();
-            anoma_path
-                .parent()
-                .map(|path| path.to_owned())
-                .unwrap_or_else(|| ".".into())
-        };
-        let mut matchmaker_dylib = dylib_dir.join(&self.matchmaker_path);
-        matchmaker_dylib.set_extension(dylib::FILE_EXT);
-        tracing::info!(
-            "Running matchmaker from {}",
-            matchmaker_dylib.to_string_lossy()
-        );
-        if !matchmaker_dylib.exists() {
-            panic!(
-                "The matchmaker library couldn't not be found. Did you build \
-                 it?"
-            )
+        // Start every configured engine on its own worker thread, which
+        // loads the engine's dylib itself. This happens before the replay
+        // below, so the replayed intents reach every configured engine.
+        // Engines can also be added (or removed) later at runtime through
+        // the admin RPC, via the same `Broker::spawn_engine` used here.
+        for engine in self.engine_configs.drain(..) {
+            self.broker.spawn_engine(engine.id, engine.matchmaker_path);
         }
-        let matchmaker_code =
-            unsafe { Library::new(matchmaker_dylib).unwrap() };
 
-        // Instantiate the matchmaker
-        let new_matchmaker: libloading::Symbol<
-            unsafe extern "C" fn() -> *mut c_void,
-        > = unsafe { matchmaker_code.get(b"_new_matchmaker").unwrap() };
+        // Replay every intent persisted by a previous run back into the
+        // freshly instantiated engines, so a restart doesn't lose track of
+        // intents that were gossiped in before it.
+        self.broker.replay_persisted_intents();
 
-        let state = MatchmakerState(Arc::new(unsafe { new_matchmaker() }));
+        // Serve the admin RPC control socket on its own thread, if one was
+        // configured, so an operator can inspect and steer the matchmaker
+        // (including adding or removing engines) without restarting it.
+        if let Some(admin_addr) = self.admin_addr {
+            admin_rpc::spawn_server(
+                admin_addr,
+                self.broker.clone(),
+                self.admin_shared_secret.clone(),
+            );
+        }
 
-        let r#impl = MatchmakerImpl {
-            state,
-            library: matchmaker_code,
-        };
+        // Probe the gossiper and ledger connections periodically so their
+        // connectivity state is observable through the admin RPC, even
+        // between reconnect attempts below.
+        supervisor::spawn_health_check(
+            self.intent_gossiper_addr,
+            self.proxy,
+            self.ledger_address.clone(),
+            self.health_check_interval,
+            self.broker.connectivity_handle(),
+        );
+
+        // Accept inbound connections from peer matchmakers, if configured
+        // to, and dial every configured peer so this matchmaker pools its
+        // mempool and matches with the rest of the mesh.
+        if let Some(mesh_addr) = self.mesh_addr {
+            self.mesh.spawn_listener(mesh_addr);
+        }
+        for peer_addr in &self.peers {
+            self.mesh.spawn_peer(*peer_addr);
+        }
+
+        // Run the listener for messages from the connected intent gossiper
+        // node. If the gossiper node restarts or the connection otherwise
+        // drops, tear it down and re-establish it with exponential backoff
+        // instead of leaving the matchmaker silently stuck. `new_pair`
+        // owns the backoff for its own retries, always seeded fresh from
+        // `health_check_interval`: a reconnect that succeeds means the
+        // ratcheting should start over, the same way `mesh::Mesh::spawn_peer`
+        // resets its own backoff on a successful reconnect.
+        let mut listener = self.listener.take().unwrap();
+        loop {
+            self.broker.connectivity_handle().lock().unwrap().gossiper =
+                supervisor::ConnectivityState::Connected;
 
-        // Run the listener for messages from the connected intent gossiper node
-        self.listener.take().unwrap().listen(|msg| match msg {
-            MsgFromServer::AddIntent { id, data } => {
-                self.try_match_intent(&r#impl, id, data);
+            if let Err(err) = listener.listen(|msg| match msg {
+                MsgFromServer::AddIntent { id, data } => {
+                    self.mesh.relay_intent(id.clone(), data.clone());
+                    self.broker.broadcast_intent(id, data);
+                }
+            }) {
+                tracing::warn!(
+                    "Matchmaker lost its gossiper connection, reconnecting \
+                     with a capped exponential backoff: {}",
+                    err
+                );
             }
-        })
-    }
 
-    /// add the intent to the matchmaker mempool and tries to find a match for
-    /// that intent
-    fn try_match_intent(
-        &self,
-        r#impl: &MatchmakerImpl,
-        intent_id: Vec<u8>,
-        intent_data: Vec<u8>,
-    ) {
-        let add_intent: libloading::Symbol<
-            unsafe extern "C" fn(
-                *mut c_void,
-                &Vec<u8>,
-                &Vec<u8>,
-            ) -> AddIntentResult,
-        > = unsafe { r#impl.library.get(b"_add_intent").unwrap() };
-
-        let result =
-            unsafe { add_intent(*r#impl.state.0, &intent_id, &intent_data) };
-
-        self.result_send.send(result).unwrap();
+            self.broker.connectivity_handle().lock().unwrap().gossiper =
+                supervisor::ConnectivityState::Reconnecting;
+            listener = ClientListener::new_pair(
+                self.intent_gossiper_addr,
+                self.proxy,
+                self.health_check_interval,
+            )
+            .0;
+        }
     }
 }
 
@@ -245,56 +533,175 @@ impl Drop for MatchmakerImpl {
 }
 
 impl ResultHandler {
+    /// Process matchmaker results until the results channel closes (every
+    /// engine's sender and the broker's own have been dropped),
+    /// submitting matched transactions to the ledger and retrying failed
+    /// submissions (on a capped exponential backoff) rather than
+    /// dropping them. Only one submission is ever in flight at a time
+    /// (each is awaited before the next one starts), so the retry queue
+    /// here never races itself against the ledger; any number of queued
+    /// submissions, up to [`retry::MAX_QUEUE_DEPTH`], may be waiting out
+    /// their backoffs meanwhile.
+    ///
+    /// The queue is a [`BinaryHeap`] ordered by `ready_at` rather than a
+    /// plain FIFO, so a freshly-queued entry with a short backoff is
+    /// always retried before an older entry that's backed off much
+    /// further out, instead of waiting behind it.
     async fn run(mut self) {
-        while let Some(result) = self.result_recv.recv().await {
-            if let Some(tx) = result.tx {
-                self.submit_tx(tx).await
+        let mut retry_queue: BinaryHeap<retry::PendingSubmission> =
+            BinaryHeap::new();
+        loop {
+            // A placeholder delay when the queue is empty; the `if` guard
+            // below keeps this branch disabled, so its exact value never
+            // matters.
+            let next_retry_at = retry_queue
+                .peek()
+                .map(|pending| pending.ready_at)
+                .unwrap_or_else(|| {
+                    tokio::time::Instant::now() + Duration::from_secs(3600)
+                });
+            let sleep = tokio::time::sleep_until(next_retry_at);
+            tokio::pin!(sleep);
+
+            tokio::select! {
+                maybe_result = self.result_recv.recv() => {
+                    let Some((engine_id, result)) = maybe_result else {
+                        break;
+                    };
+                    if let Some(tx_data) = result.tx {
+                        self.try_submit_or_enqueue(
+                            &mut retry_queue,
+                            engine_id,
+                            tx_data,
+                            0,
+                        )
+                        .await;
+                    }
+                    if let Some(intent_ids) = result.matched_intents {
+                        self.mesh.relay_consumed(intent_ids.clone());
+                        self.dialer.send(MsgFromClient::Matched { intent_ids });
+                    }
+                }
+                _ = &mut sleep, if !retry_queue.is_empty() => {
+                    let pending = retry_queue.pop().unwrap();
+                    self.retrying_submissions
+                        .store(retry_queue.len() as u64, Ordering::Relaxed);
+                    self.try_submit_or_enqueue(
+                        &mut retry_queue,
+                        pending.engine_id,
+                        pending.tx_data,
+                        pending.attempt,
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+
+    /// Try to submit the transaction built from `tx_data`; on failure,
+    /// re-queue it for retry with a backed-off `ready_at` unless it has
+    /// already exhausted its attempts or the queue is full, in which case
+    /// the match is abandoned with a warning.
+    async fn try_submit_or_enqueue(
+        &self,
+        retry_queue: &mut BinaryHeap<retry::PendingSubmission>,
+        engine_id: broker::EngineId,
+        tx_data: Vec<u8>,
+        attempt: u32,
+    ) {
+        if let Err(err) = self.submit_tx(&engine_id, &tx_data).await {
+            if attempt + 1 >= retry::MAX_ATTEMPTS {
+                tracing::warn!(
+                    "Abandoning a matched trade from matchmaker engine \
+                     {:?} after {} failed submission attempt(s): {}",
+                    engine_id,
+                    attempt + 1,
+                    err
+                );
+                return;
             }
-            if let Some(intent_ids) = result.matched_intents {
-                self.dialer.send(MsgFromClient::Matched { intent_ids })
+            if retry_queue.len() >= retry::MAX_QUEUE_DEPTH {
+                tracing::warn!(
+                    "Abandoning a matched trade from matchmaker engine \
+                     {:?}: the submission retry queue is full ({} \
+                     pending)",
+                    engine_id,
+                    retry_queue.len()
+                );
+                return;
             }
+
+            let backoff = retry::backoff_for(attempt);
+            tracing::warn!(
+                "Matchmaker engine {:?} failed to submit a transaction \
+                 (attempt {}), retrying in {:?}: {}",
+                engine_id,
+                attempt + 1,
+                backoff,
+                err
+            );
+            retry_queue.push(retry::PendingSubmission {
+                engine_id,
+                tx_data,
+                attempt: attempt + 1,
+                ready_at: tokio::time::Instant::now() + backoff,
+            });
+            self.retrying_submissions
+                .store(retry_queue.len() as u64, Ordering::Relaxed);
         }
     }
 
-    async fn submit_tx(&self, tx_data: Vec<u8>) {
+    /// Build a `WrapperTx` from `tx_data` with a freshly queried epoch
+    /// and broadcast it to the ledger.
+    async fn submit_tx(
+        &self,
+        engine_id: &broker::EngineId,
+        tx_data: &[u8],
+    ) -> Result<(), String> {
         let tx_code = self.tx_code.clone();
-        let matches = MatchedExchanges::try_from_slice(&tx_data[..]).unwrap();
+        let matches = MatchedExchanges::try_from_slice(tx_data).unwrap();
         let intent_transfers = IntentTransfers {
             matches,
             source: self.tx_source_address.clone(),
         };
         let tx_data = intent_transfers.try_to_vec().unwrap();
+        let epoch = rpc::query_epoch(
+            args::Query {
+                ledger_address: self.ledger_address.clone(),
+            },
+            self.proxy,
+        )
+        .await
+        .map_err(|err| format!("Failed to query the ledger's epoch: {}", err))?;
         let tx = WrapperTx::new(
             Fee {
                 amount: 0.into(),
                 token: address::xan(),
             },
             &self.tx_signing_key,
-            rpc::query_epoch(args::Query {
-                ledger_address: self.ledger_address.clone(),
-            })
-            .await,
+            epoch,
             0.into(),
             Tx::new(tx_code, Some(tx_data)).sign(&self.tx_signing_key),
         );
 
-        let response =
-            broadcast_tx(self.ledger_address.clone(), tx, &self.tx_signing_key)
-                .await;
+        let response = broadcast_tx(
+            self.ledger_address.clone(),
+            tx,
+            &self.tx_signing_key,
+            self.proxy,
+        )
+        .await;
         match response {
             Ok(tx_response) => {
                 tracing::info!(
-                    "Injected transaction from matchmaker with result: {:#?}",
+                    "Injected transaction from matchmaker engine {:?} with \
+                     result: {:#?}",
+                    engine_id,
                     tx_response
                 );
+                Ok(())
             }
-            Err(err) => {
-                tracing::error!(
-                    "Matchmaker error in submitting a transaction to the \
-                     ledger: {}",
-                    err
-                );
-            }
+            Err(err) => Err(err.to_string()),
         }
     }
 }
@@ -0,0 +1,256 @@
+//! A small request/response control protocol for a running matchmaker,
+//! served over a local TCP socket alongside the intent gossiper connection.
+//! It lets an operator inspect and steer the matchmaker (list what's
+//! pending, inject an intent by hand, pull stats, drop a stuck intent)
+//! without having to restart the process. The companion `matchmaker-cli`
+//! binary is a thin client for this protocol.
+//!
+//! Connections authenticate with a shared secret sent as the first frame
+//! on connect, the same way [`super::mesh`] authenticates peer mesh
+//! connections: this isn't meant to withstand a serious attacker, just
+//! to stop any process that can merely reach `admin_addr` from listing,
+//! injecting or dropping intents, and in particular from loading an
+//! arbitrary dylib via [`AdminRequest::AddEngine`].
+
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::broker::{Broker, EngineId};
+use super::framing::{read_framed, write_framed};
+use super::supervisor::ConnectivityReport;
+
+/// A request sent to a running matchmaker's admin socket.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub enum AdminRequest {
+    /// List the IDs of intents the matchmaker currently holds.
+    ListPendingIntents,
+    /// Inject an intent as if it had arrived from the gossiper.
+    InjectIntent {
+        /// The Borsh-encoded intent data.
+        data: Vec<u8>,
+    },
+    /// Fetch a few counters about the matchmaker's current state.
+    Stats,
+    /// Evict an intent from the matchmaker's local bookkeeping.
+    DropIntent {
+        /// The ID of the intent to drop.
+        id: Vec<u8>,
+    },
+    /// List the IDs of the engines currently hosted by the matchmaker.
+    ListEngines,
+    /// Start a new engine from its dylib and register it under `id`,
+    /// replacing any existing engine with the same ID.
+    AddEngine {
+        /// Identifies the new engine among the others hosted by the
+        /// matchmaker.
+        id: EngineId,
+        /// Path of the engine's dylib, relative to the Anoma binary
+        /// directory.
+        matchmaker_path: String,
+    },
+    /// Stop forwarding intents to an engine, without disturbing the
+    /// others.
+    RemoveEngine {
+        /// The ID of the engine to remove.
+        id: EngineId,
+    },
+}
+
+/// The first frame sent on every admin connection, authenticating it
+/// before any [`AdminRequest`] is accepted.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+struct AdminHello {
+    shared_secret: String,
+}
+
+/// The response to an [`AdminRequest`].
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub enum AdminResponse {
+    /// Reply to [`AdminRequest::ListPendingIntents`].
+    PendingIntents {
+        /// IDs of the intents currently held by the matchmaker.
+        ids: Vec<Vec<u8>>,
+    },
+    /// Reply to [`AdminRequest::InjectIntent`], carrying the ID that was
+    /// assigned to the injected intent.
+    Injected {
+        /// The ID assigned to the injected intent.
+        id: Vec<u8>,
+    },
+    /// Reply to [`AdminRequest::Stats`].
+    Stats {
+        /// Number of intents currently held by the matchmaker.
+        pending_intents: u64,
+        /// Connectivity to the gossiper and ledger.
+        connectivity: ConnectivityReport,
+        /// IDs of the engines these intents are fanned out to.
+        engines: Vec<EngineId>,
+        /// Number of transaction submissions currently queued for retry
+        /// after a failed broadcast, so operators can see backpressure
+        /// building on the ledger connection.
+        retrying_submissions: u64,
+    },
+    /// Reply to [`AdminRequest::DropIntent`].
+    Dropped {
+        /// The ID that a drop was requested for.
+        id: Vec<u8>,
+        /// Whether that ID was actually known to the matchmaker.
+        existed: bool,
+    },
+    /// Reply to [`AdminRequest::ListEngines`].
+    Engines {
+        /// IDs of the engines currently hosted by the matchmaker.
+        ids: Vec<EngineId>,
+    },
+    /// Reply to [`AdminRequest::AddEngine`].
+    EngineAdded {
+        /// The ID the new engine was registered under.
+        id: EngineId,
+    },
+    /// Reply to [`AdminRequest::RemoveEngine`].
+    EngineRemoved {
+        /// The ID that a removal was requested for.
+        id: EngineId,
+        /// Whether that ID was actually hosted by the matchmaker.
+        existed: bool,
+    },
+    /// The request could not be served.
+    Error {
+        /// A human readable description of what went wrong.
+        message: String,
+    },
+}
+
+/// Bind `admin_addr` and serve [`AdminRequest`]s on a dedicated thread until
+/// the process exits. Each connection authenticates with `shared_secret`
+/// before anything else, then is served a single request and closed.
+pub fn spawn_server(
+    admin_addr: SocketAddr,
+    broker: Broker,
+    shared_secret: String,
+) {
+    std::thread::spawn(move || {
+        let listener = match TcpListener::bind(admin_addr) {
+            Ok(listener) => listener,
+            Err(err) => {
+                tracing::error!(
+                    "Matchmaker admin RPC failed to bind {}: {}",
+                    admin_addr,
+                    err
+                );
+                return;
+            }
+        };
+        tracing::info!("Matchmaker admin RPC listening on {}", admin_addr);
+
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    tracing::warn!(
+                        "Matchmaker admin RPC accept error: {}",
+                        err
+                    );
+                    continue;
+                }
+            };
+            let broker = broker.clone();
+            let shared_secret = shared_secret.clone();
+            std::thread::spawn(move || {
+                if let Err(err) =
+                    handle_connection(&mut stream, &broker, &shared_secret)
+                {
+                    tracing::warn!(
+                        "Matchmaker admin RPC connection error: {}",
+                        err
+                    );
+                }
+            });
+        }
+    });
+}
+
+fn handle_connection(
+    stream: &mut TcpStream,
+    broker: &Broker,
+    shared_secret: &str,
+) -> io::Result<()> {
+    let hello: AdminHello = read_framed(stream)?;
+    if hello.shared_secret != shared_secret {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "admin RPC shared secret mismatch",
+        ));
+    }
+
+    let request: AdminRequest = read_framed(stream)?;
+    let response = handle_request(request, broker);
+    write_framed(stream, &response)
+}
+
+fn handle_request(request: AdminRequest, broker: &Broker) -> AdminResponse {
+    match request {
+        AdminRequest::ListPendingIntents => {
+            let ids = broker.pending_intent_ids();
+            AdminResponse::PendingIntents { ids }
+        }
+        AdminRequest::InjectIntent { data } => {
+            let id = broker.inject_intent(data);
+            AdminResponse::Injected { id }
+        }
+        AdminRequest::Stats => {
+            let pending_intents = broker.pending_intent_count();
+            let connectivity = broker.connectivity();
+            let engines = broker.engine_ids();
+            let retrying_submissions = broker.retrying_submissions();
+            AdminResponse::Stats {
+                pending_intents,
+                connectivity,
+                engines,
+                retrying_submissions,
+            }
+        }
+        AdminRequest::DropIntent { id } => {
+            let existed = broker.intent_exists(&id);
+            if existed {
+                broker.drop_intent(&id);
+            }
+            AdminResponse::Dropped { id, existed }
+        }
+        AdminRequest::ListEngines => {
+            AdminResponse::Engines {
+                ids: broker.engine_ids(),
+            }
+        }
+        AdminRequest::AddEngine { id, matchmaker_path } => {
+            broker.spawn_engine(id.clone(), PathBuf::from(matchmaker_path));
+            AdminResponse::EngineAdded { id }
+        }
+        AdminRequest::RemoveEngine { id } => {
+            let existed = broker.remove_engine(&id);
+            AdminResponse::EngineRemoved { id, existed }
+        }
+    }
+}
+
+/// Send a single [`AdminRequest`] to the matchmaker admin socket at
+/// `admin_addr`, authenticating with `shared_secret`, and return the
+/// decoded [`AdminResponse`]. Used by the `matchmaker-cli` binary.
+pub fn send_request(
+    admin_addr: SocketAddr,
+    shared_secret: &str,
+    request: &AdminRequest,
+) -> io::Result<AdminResponse> {
+    let mut stream = TcpStream::connect(admin_addr)?;
+    write_framed(
+        &mut stream,
+        &AdminHello {
+            shared_secret: shared_secret.to_owned(),
+        },
+    )?;
+    write_framed(&mut stream, request)?;
+    read_framed(&mut stream)
+}
@@ -0,0 +1,228 @@
+//! Fans the intents received from the gossiper out to every matchmaker
+//! engine hosted by this process, so a single node can run several
+//! matchmaker implementations (e.g. different trading strategies) against
+//! the same intent stream. This mirrors the classic
+//! accept-loop/broker-loop/worker pattern: the gossip listen loop in
+//! [`super::Runner::listen`] is the accept loop, [`Broker`] is the broker,
+//! and each engine's dedicated thread (running [`super::run_engine`]) is a
+//! worker.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+use anoma::types::matchmaker::AddIntentResult;
+use borsh::{BorshDeserialize, BorshSerialize};
+use tokio::sync::oneshot;
+
+use super::{derive_injected_intent_id, run_engine, MatchmakerMessage};
+use super::persistence;
+use super::supervisor;
+
+/// Identifies one matchmaker engine hosted by a broker.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize,
+)]
+pub struct EngineId(pub String);
+
+/// Configuration for one matchmaker engine hosted by the broker.
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    /// Identifies this engine among the others hosted by the same broker.
+    pub id: EngineId,
+    /// Path of the engine's dylib, relative to the Anoma binary directory.
+    pub matchmaker_path: PathBuf,
+}
+
+/// Fans incoming intents out to every matchmaker engine hosted by this
+/// process, and tags their [`AddIntentResult`]s with the originating
+/// [`EngineId`] for the [`super::ResultHandler`]. Cloneable and shared
+/// between the gossip accept loop and the admin RPC server, so operators
+/// can add or remove engines at runtime without disturbing the others.
+#[derive(Clone)]
+pub(crate) struct Broker {
+    engines: Arc<Mutex<HashMap<EngineId, mpsc::Sender<MatchmakerMessage>>>>,
+    result_send:
+        tokio::sync::mpsc::UnboundedSender<(EngineId, AddIntentResult)>,
+    intent_store: Arc<persistence::IntentStore>,
+    connectivity: supervisor::SharedConnectivity,
+    /// Number of transaction submissions currently queued for retry by
+    /// the [`super::ResultHandler`]. Shared so it can be reported through
+    /// the admin RPC without the broker and the result handler needing a
+    /// reference to each other.
+    retrying_submissions: Arc<AtomicU64>,
+}
+
+impl Broker {
+    pub(crate) fn new(
+        result_send: tokio::sync::mpsc::UnboundedSender<(
+            EngineId,
+            AddIntentResult,
+        )>,
+        intent_store: Arc<persistence::IntentStore>,
+        connectivity: supervisor::SharedConnectivity,
+        retrying_submissions: Arc<AtomicU64>,
+    ) -> Self {
+        Self {
+            engines: Default::default(),
+            result_send,
+            intent_store,
+            connectivity,
+            retrying_submissions,
+        }
+    }
+
+    /// Start a new engine from `matchmaker_path` on its own worker thread
+    /// and register it under `id`, replacing any existing engine with the
+    /// same ID.
+    pub(crate) fn spawn_engine(&self, id: EngineId, matchmaker_path: PathBuf) {
+        let (message_send, message_recv) = mpsc::channel();
+        let result_send = self.result_send.clone();
+        let intent_store = self.intent_store.clone();
+        let engine_id = id.clone();
+        std::thread::spawn(move || {
+            run_engine(
+                engine_id,
+                matchmaker_path,
+                message_recv,
+                result_send,
+                intent_store,
+            );
+        });
+        self.engines.lock().unwrap().insert(id, message_send);
+    }
+
+    /// Stop forwarding intents to `id`'s engine. Its worker thread notices
+    /// the channel close and exits on its own.
+    ///
+    /// Also resolves `id` out of the intent store's pending-engines
+    /// bookkeeping, so an intent that was fanned out to it but not yet
+    /// matched doesn't sit waiting forever for a check-in from an engine
+    /// that's no longer registered.
+    pub(crate) fn remove_engine(&self, id: &EngineId) -> bool {
+        let existed = self.engines.lock().unwrap().remove(id).is_some();
+        if existed {
+            self.intent_store.remove_engine(id);
+        }
+        existed
+    }
+
+    /// IDs of every engine currently registered with the broker.
+    pub(crate) fn engine_ids(&self) -> Vec<EngineId> {
+        self.engines.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Fan an intent out to every registered engine, waiting for each one
+    /// to acknowledge receipt before moving on to the next so the broker
+    /// never races ahead of a slow engine.
+    ///
+    /// The set of engines the intent is persisted against is fixed here,
+    /// once, to exactly the engines registered at this moment: that's
+    /// the set [`persistence::IntentStore::consume`] waits to hear from
+    /// before it considers the intent fully matched and evicts it.
+    pub(crate) fn broadcast_intent(
+        &self,
+        intent_id: Vec<u8>,
+        intent_data: Vec<u8>,
+    ) {
+        let senders: Vec<(EngineId, mpsc::Sender<MatchmakerMessage>)> = self
+            .engines
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, sender)| (id.clone(), sender.clone()))
+            .collect();
+        let engine_ids: Vec<EngineId> =
+            senders.iter().map(|(id, _)| id.clone()).collect();
+        self.intent_store.put(&intent_id, &intent_data, &engine_ids);
+
+        for (_, message_send) in senders {
+            let (ack_send, ack_recv) = oneshot::channel();
+            let sent = message_send.send(MatchmakerMessage::ApplyIntent {
+                id: intent_id.clone(),
+                data: intent_data.clone(),
+                ack: ack_send,
+            });
+            if sent.is_ok() {
+                let _ = ack_recv.blocking_recv();
+            }
+            // A closed channel means the engine's worker has already
+            // exited (e.g. it was just removed); nothing to wait for.
+        }
+    }
+
+    /// Inject an intent as if it had arrived from the gossiper, deriving a
+    /// synthetic ID for it since admin-injected intents don't come with one
+    /// already assigned.
+    pub(crate) fn inject_intent(&self, data: Vec<u8>) -> Vec<u8> {
+        let id = derive_injected_intent_id(&data);
+        self.broadcast_intent(id.clone(), data);
+        id
+    }
+
+    /// IDs of every intent currently persisted across all engines.
+    pub(crate) fn pending_intent_ids(&self) -> Vec<Vec<u8>> {
+        self.intent_store.ids()
+    }
+
+    /// Number of intents currently persisted across all engines.
+    pub(crate) fn pending_intent_count(&self) -> u64 {
+        self.intent_store.len() as u64
+    }
+
+    /// Whether `id` is currently persisted.
+    pub(crate) fn intent_exists(&self, id: &[u8]) -> bool {
+        self.intent_store.contains(id)
+    }
+
+    /// Evict an intent from the shared intent store and from every
+    /// hosted engine's own in-memory match graph, so it's actually gone
+    /// rather than just missing its RocksDB backup (which would let an
+    /// engine match and resubmit it again regardless).
+    pub(crate) fn drop_intent(&self, id: &[u8]) {
+        self.intent_store.remove(id);
+        let senders: Vec<_> =
+            self.engines.lock().unwrap().values().cloned().collect();
+        for message_send in senders {
+            let _ = message_send
+                .send(MatchmakerMessage::RemoveIntent { id: id.to_vec() });
+        }
+    }
+
+    /// A point-in-time snapshot of the gossiper and ledger connectivity.
+    pub(crate) fn connectivity(&self) -> supervisor::ConnectivityReport {
+        *self.connectivity.lock().unwrap()
+    }
+
+    /// A clone of the shared connectivity state, e.g. to hand to the
+    /// health check supervisor.
+    pub(crate) fn connectivity_handle(&self) -> supervisor::SharedConnectivity {
+        self.connectivity.clone()
+    }
+
+    /// Number of transaction submissions currently queued for retry,
+    /// i.e. a `broadcast_tx` has failed for them at least once and
+    /// they're awaiting their next backed-off attempt.
+    pub(crate) fn retrying_submissions(&self) -> u64 {
+        self.retrying_submissions.load(Ordering::Relaxed)
+    }
+
+    /// Replay every intent persisted by a previous run into every
+    /// currently registered engine, so a restart doesn't lose track of
+    /// intents that were gossiped in before it.
+    pub(crate) fn replay_persisted_intents(&self) {
+        let persisted: Vec<_> = self.intent_store.iter().collect();
+        if !persisted.is_empty() {
+            tracing::info!(
+                "Replaying {} persisted intent(s) into {} engine(s)",
+                persisted.len(),
+                self.engine_ids().len()
+            );
+        }
+        for (intent_id, intent_data) in persisted {
+            self.broadcast_intent(intent_id, intent_data);
+        }
+    }
+}
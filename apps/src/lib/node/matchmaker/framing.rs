@@ -0,0 +1,79 @@
+//! Length-prefixed Borsh framing shared by every socket protocol the
+//! matchmaker speaks to another process over ([`super::admin_rpc`],
+//! [`super::mesh`], and [`crate::node::gossip::matchmakers`]): a 4-byte
+//! big-endian length followed by that many bytes of Borsh-encoded
+//! payload.
+//!
+//! The length is read off the wire before any authentication has
+//! happened, so it's attacker-controlled on both the admin socket and an
+//! unauthenticated mesh connection; [`read_framed`] rejects a length
+//! over [`MAX_FRAME_SIZE`] before allocating a buffer for it, so a
+//! single connection can't force a multi-gigabyte allocation just by
+//! sending a large length.
+
+use std::io::{self, Read, Write};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// The largest frame [`read_framed`] will allocate a buffer for. Well
+/// above any legitimate admin request or gossiped intent, but far short
+/// of the ~4GiB a bare `u32` length would otherwise allow.
+const MAX_FRAME_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Read one length-prefixed, Borsh-encoded value from `stream`.
+pub(crate) fn read_framed<T: BorshDeserialize>(
+    stream: &mut impl Read,
+) -> io::Result<T> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let len = u32::from_be_bytes(len_buf);
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "frame of {} bytes exceeds the {} byte limit",
+                len, MAX_FRAME_SIZE
+            ),
+        ));
+    }
+    let mut buf = vec![0u8; len as usize];
+    stream.read_exact(&mut buf)?;
+    T::try_from_slice(&buf)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Write one length-prefixed, Borsh-encoded value to `stream`.
+pub(crate) fn write_framed<T: BorshSerialize>(
+    stream: &mut impl Write,
+    value: &T,
+) -> io::Result<()> {
+    let buf = value
+        .try_to_vec()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    stream.write_all(&(buf.len() as u32).to_be_bytes())?;
+    stream.write_all(&buf)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn round_trips_a_value() {
+        let mut buf = Vec::new();
+        write_framed(&mut buf, &vec![1u8, 2, 3]).unwrap();
+        let value: Vec<u8> = read_framed(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(value, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_an_oversized_length_before_allocating() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(MAX_FRAME_SIZE + 1).to_be_bytes());
+        let result: io::Result<Vec<u8>> = read_framed(&mut Cursor::new(buf));
+        assert!(result.is_err());
+    }
+}
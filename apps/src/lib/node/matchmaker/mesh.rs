@@ -0,0 +1,392 @@
+//! An optional full-mesh peering layer between matchmakers, so the
+//! liquidity seen by one matchmaker's gossiper connection gets pooled
+//! across every matchmaker in the mesh instead of staying siloed to
+//! whichever one received it. Every matchmaker in the mesh is expected to
+//! maintain a direct connection to every other one (hence "full mesh"),
+//! so a single broadcast from the matchmaker that first saw an intent (or
+//! consumed one) is enough to reach the whole mesh in one hop.
+//!
+//! Connections authenticate with a shared secret exchanged as the first
+//! message on connect; this isn't meant to withstand a serious attacker,
+//! just to keep a misconfigured or unrelated process from joining the
+//! mesh. Churn is handled by reconnecting with a capped exponential
+//! backoff (starting at 1s, capped at 60s), like the one
+//! [`super::Runner::listen`] uses for the gossiper connection. Each
+//! connection keeps a bounded FIFO of the intent IDs it has seen, and an
+//! intent still held in the intent store is not applied again. The FIFO
+//! starts empty on every new connection, so an intent a peer replays
+//! after reconnecting is only skipped while it is still in the store.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::broker::Broker;
+use super::framing::{read_framed, write_framed};
+
+/// How many recently seen intent IDs to remember per peer connection
+/// before the oldest ones are evicted, bounding memory. The IDs belong to
+/// one connection and are not carried over to a reconnect.
+const PER_PEER_SEEN_CAPACITY: usize = 4096;
+
+/// Message exchanged between matchmakers over a peer mesh connection.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+enum PeerMessage {
+    /// Sent once, immediately after connecting, to authenticate and (if
+    /// the sender accepts inbound peer connections of its own) announce
+    /// where it can be reached.
+    Hello {
+        shared_secret: String,
+        listen_addr: Option<SocketAddr>,
+    },
+    /// A new intent this matchmaker has just seen, for the peer to apply
+    /// to its own engines.
+    NewIntent { id: Vec<u8>, data: Vec<u8> },
+    /// IDs a match has just consumed; the peer should evict them from its
+    /// own intent store too, so it doesn't also try to submit a
+    /// transaction for an intent that's already been matched elsewhere.
+    ConsumedIntents { ids: Vec<Vec<u8>> },
+}
+
+/// A bounded FIFO of recently seen intent IDs.
+#[derive(Default)]
+struct SeenIds {
+    order: VecDeque<Vec<u8>>,
+    set: HashSet<Vec<u8>>,
+}
+
+impl SeenIds {
+    /// Record `id` as seen, returning whether it wasn't already.
+    fn insert(&mut self, id: Vec<u8>) -> bool {
+        if !self.set.insert(id.clone()) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > PER_PEER_SEEN_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.set.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod seen_ids_tests {
+    use super::*;
+
+    #[test]
+    fn reports_only_the_first_insert_as_new() {
+        let mut seen = SeenIds::default();
+        assert!(seen.insert(vec![1]));
+        assert!(!seen.insert(vec![1]));
+        assert!(seen.insert(vec![2]));
+    }
+
+    #[test]
+    fn evicts_the_oldest_once_over_capacity() {
+        let mut seen = SeenIds::default();
+        let first = vec![0, 0];
+        for i in 0..PER_PEER_SEEN_CAPACITY {
+            let id = vec![(i >> 8) as u8, i as u8];
+            assert!(seen.insert(id));
+        }
+        assert_eq!(seen.order.len(), PER_PEER_SEEN_CAPACITY);
+
+        // One more insert evicts `first`, the oldest entry, so it's
+        // reported as new again if it comes back around.
+        seen.insert(vec![0xff, 0xff]);
+        assert_eq!(seen.order.len(), PER_PEER_SEEN_CAPACITY);
+        assert!(seen.insert(first));
+    }
+}
+
+/// This matchmaker's side of one peer connection.
+#[derive(Clone)]
+struct PeerHandle {
+    message_send: mpsc::Sender<PeerMessage>,
+    /// IDs already relayed to this peer, so a retried or replayed gossip
+    /// event isn't sent to it twice.
+    relayed: Arc<Mutex<SeenIds>>,
+}
+
+/// A handle to the peer mesh, shared between the gossip accept loop (which
+/// relays freshly seen intents to every peer) and the
+/// [`super::ResultHandler`] (which broadcasts consumed notifications once
+/// a match fires). Cheap to clone; every clone refers to the same set of
+/// peer connections.
+#[derive(Clone)]
+pub(crate) struct Mesh {
+    /// Where this matchmaker can be reached by other peers, if anywhere.
+    own_addr: Option<SocketAddr>,
+    shared_secret: String,
+    /// Applies intents and evictions relayed in by peers.
+    broker: Broker,
+    peers: Arc<Mutex<HashMap<SocketAddr, PeerHandle>>>,
+}
+
+impl Mesh {
+    pub(crate) fn new(
+        own_addr: Option<SocketAddr>,
+        shared_secret: String,
+        broker: Broker,
+    ) -> Self {
+        Self {
+            own_addr,
+            shared_secret,
+            broker,
+            peers: Default::default(),
+        }
+    }
+
+    /// Accept inbound peer connections on `mesh_addr` until the process
+    /// exits.
+    pub(crate) fn spawn_listener(&self, mesh_addr: SocketAddr) {
+        let mesh = self.clone();
+        std::thread::spawn(move || {
+            let listener = match TcpListener::bind(mesh_addr) {
+                Ok(listener) => listener,
+                Err(err) => {
+                    tracing::error!(
+                        "Matchmaker peer mesh failed to bind {}: {}",
+                        mesh_addr,
+                        err
+                    );
+                    return;
+                }
+            };
+            tracing::info!("Matchmaker peer mesh listening on {}", mesh_addr);
+
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        tracing::warn!(
+                            "Matchmaker peer mesh accept error: {}",
+                            err
+                        );
+                        continue;
+                    }
+                };
+                let mesh = mesh.clone();
+                std::thread::spawn(move || mesh.accept_connection(stream));
+            }
+        });
+    }
+
+    fn accept_connection(&self, mut stream: TcpStream) {
+        let peer_addr = match stream.peer_addr() {
+            Ok(addr) => addr,
+            Err(err) => {
+                tracing::warn!(
+                    "Matchmaker peer mesh couldn't read the peer address: {}",
+                    err
+                );
+                return;
+            }
+        };
+        match self.handshake(&mut stream) {
+            Ok(listen_addr) => {
+                self.run_connection(listen_addr.unwrap_or(peer_addr), stream)
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Matchmaker peer mesh handshake with {} failed: {}",
+                    peer_addr,
+                    err
+                );
+            }
+        }
+    }
+
+    /// Dial `peer_addr` and keep the connection alive, reconnecting with
+    /// exponential backoff (capped at 60s) if it drops or can't be
+    /// established.
+    pub(crate) fn spawn_peer(&self, peer_addr: SocketAddr) {
+        let mesh = self.clone();
+        std::thread::spawn(move || {
+            let mut backoff = Duration::from_secs(1);
+            loop {
+                match TcpStream::connect(peer_addr) {
+                    Ok(mut stream) => match mesh.handshake(&mut stream) {
+                        Ok(_) => {
+                            backoff = Duration::from_secs(1);
+                            mesh.run_connection(peer_addr, stream);
+                        }
+                        Err(err) => {
+                            tracing::warn!(
+                                "Matchmaker peer mesh handshake with {} \
+                                 failed: {}",
+                                peer_addr,
+                                err
+                            );
+                        }
+                    },
+                    Err(err) => {
+                        tracing::warn!(
+                            "Matchmaker peer mesh couldn't connect to {}: {}",
+                            peer_addr,
+                            err
+                        );
+                    }
+                }
+
+                std::thread::sleep(backoff);
+                backoff = (backoff * 2).min(Duration::from_secs(60));
+            }
+        });
+    }
+
+    /// Exchange `Hello`s with whatever is on the other end of `stream`,
+    /// returning the peer's advertised listen address, if it announced
+    /// one.
+    fn handshake(
+        &self,
+        stream: &mut TcpStream,
+    ) -> io::Result<Option<SocketAddr>> {
+        write_framed(
+            stream,
+            &PeerMessage::Hello {
+                shared_secret: self.shared_secret.clone(),
+                listen_addr: self.own_addr,
+            },
+        )?;
+        match read_framed(stream)? {
+            PeerMessage::Hello {
+                shared_secret,
+                listen_addr,
+            } => {
+                if shared_secret != self.shared_secret {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "peer mesh shared secret mismatch",
+                    ));
+                }
+                Ok(listen_addr)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected a Hello as the first message",
+            )),
+        }
+    }
+
+    /// Register a handshaken connection under `key` and run it until it
+    /// drops, applying whatever the peer relays in and forwarding this
+    /// matchmaker's own relayed messages out.
+    fn run_connection(&self, key: SocketAddr, stream: TcpStream) {
+        let write_stream = match stream.try_clone() {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!(
+                    "Matchmaker peer mesh couldn't clone the connection to \
+                     {}: {}",
+                    key,
+                    err
+                );
+                return;
+            }
+        };
+
+        let (message_send, message_recv) = mpsc::channel();
+        self.peers.lock().unwrap().insert(
+            key,
+            PeerHandle {
+                message_send,
+                relayed: Default::default(),
+            },
+        );
+        tracing::info!("Matchmaker peer mesh connected to {}", key);
+
+        std::thread::spawn(move || {
+            run_writer(key, write_stream, message_recv);
+        });
+
+        self.run_reader(key, stream);
+
+        self.peers.lock().unwrap().remove(&key);
+        tracing::info!("Matchmaker peer mesh disconnected from {}", key);
+    }
+
+    fn run_reader(&self, key: SocketAddr, mut stream: TcpStream) {
+        let mut seen = SeenIds::default();
+        loop {
+            match read_framed(&mut stream) {
+                Ok(PeerMessage::NewIntent { id, data }) => {
+                    if seen.insert(id.clone())
+                        && !self.broker.intent_exists(&id)
+                    {
+                        self.broker.broadcast_intent(id, data);
+                    }
+                }
+                Ok(PeerMessage::ConsumedIntents { ids }) => {
+                    for id in ids {
+                        self.broker.drop_intent(&id);
+                    }
+                }
+                Ok(PeerMessage::Hello { .. }) => {
+                    tracing::warn!(
+                        "Matchmaker peer mesh got an unexpected Hello from \
+                         {}",
+                        key
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Matchmaker peer mesh connection to {} dropped: {}",
+                        key,
+                        err
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Relay a freshly seen intent to every connected peer that hasn't
+    /// already been sent it.
+    pub(crate) fn relay_intent(&self, id: Vec<u8>, data: Vec<u8>) {
+        for handle in self.peers.lock().unwrap().values() {
+            if handle.relayed.lock().unwrap().insert(id.clone()) {
+                let _ = handle.message_send.send(PeerMessage::NewIntent {
+                    id: id.clone(),
+                    data: data.clone(),
+                });
+            }
+        }
+    }
+
+    /// Broadcast a "consumed" notification for `ids` to every connected
+    /// peer, so they evict them from their own intent stores instead of
+    /// later trying to submit a transaction for an intent that's already
+    /// been matched.
+    pub(crate) fn relay_consumed(&self, ids: Vec<Vec<u8>>) {
+        for handle in self.peers.lock().unwrap().values() {
+            let _ = handle.message_send.send(PeerMessage::ConsumedIntents {
+                ids: ids.clone(),
+            });
+        }
+    }
+}
+
+fn run_writer(
+    key: SocketAddr,
+    mut stream: TcpStream,
+    message_recv: mpsc::Receiver<PeerMessage>,
+) {
+    while let Ok(message) = message_recv.recv() {
+        if let Err(err) = write_framed(&mut stream, &message) {
+            tracing::warn!(
+                "Matchmaker peer mesh write to {} failed: {}",
+                key,
+                err
+            );
+            return;
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! A RocksDB-backed, crash-surviving record of every intent handed to the
+//! matchmaker dylib, keyed by intent ID. The dylib's own mempool lives only
+//! in memory behind a raw pointer and is lost on crash or restart, so
+//! without this every intent gossiped in before a restart would have to be
+//! re-gossiped from scratch.
+//!
+//! Since the broker now fans an intent out to every hosted engine, an
+//! intent is only truly done with once every engine it was handed to has
+//! independently matched it (different engines run different strategies,
+//! so one matching doesn't mean the others are finished with it). A
+//! second column family tracks, per intent, which engines still haven't
+//! consumed it; the `intents` entry itself is only evicted once that set
+//! is empty.
+
+use std::path::Path;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, IteratorMode, Options, DB};
+
+use super::broker::EngineId;
+
+/// Column family holding `intent_id -> intent_data` entries.
+const INTENTS_CF: &str = "intents";
+
+/// Column family holding `intent_id -> Borsh-encoded Vec<EngineId>`
+/// entries, tracking which engines still haven't consumed a match for
+/// that intent.
+const PENDING_ENGINES_CF: &str = "pending_engines";
+
+/// A persistent record of intents forwarded to the matchmaker dylib.
+pub(crate) struct IntentStore {
+    db: DB,
+}
+
+impl IntentStore {
+    /// Open (creating if necessary) the intent store at `path`.
+    ///
+    /// A corrupt or already-locked database panics here rather than being
+    /// swallowed: silently falling back to an empty store would mean every
+    /// write from then on is a silent no-op, quietly dropping intents that
+    /// the matchmaker believes it has safely persisted.
+    pub(crate) fn open(path: impl AsRef<Path>) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+        let intents_cf =
+            ColumnFamilyDescriptor::new(INTENTS_CF, Options::default());
+        let pending_engines_cf = ColumnFamilyDescriptor::new(
+            PENDING_ENGINES_CF,
+            Options::default(),
+        );
+
+        let db = DB::open_cf_descriptors(&db_opts, path.as_ref(), vec![
+            intents_cf,
+            pending_engines_cf,
+        ])
+        .unwrap_or_else(|err| {
+            panic!(
+                "Failed to open the matchmaker's intent store at {}: {}. \
+                 Refusing to start, since doing so could silently drop \
+                 intents instead of persisting them.",
+                path.as_ref().to_string_lossy(),
+                err
+            )
+        });
+
+        Self { db }
+    }
+
+    fn cf(&self) -> &ColumnFamily {
+        self.db
+            .cf_handle(INTENTS_CF)
+            .expect("the intents column family is created on open")
+    }
+
+    fn pending_engines_cf(&self) -> &ColumnFamily {
+        self.db
+            .cf_handle(PENDING_ENGINES_CF)
+            .expect("the pending_engines column family is created on open")
+    }
+
+    /// Persist an intent along with the set of engines it was fanned out
+    /// to. Called before it is handed to any engine, so a crash right
+    /// after never loses track of it.
+    pub(crate) fn put(
+        &self,
+        intent_id: &[u8],
+        intent_data: &[u8],
+        engine_ids: &[EngineId],
+    ) {
+        self.db.put_cf(self.cf(), intent_id, intent_data).unwrap_or_else(
+            |err| {
+                panic!(
+                    "Failed to persist intent {}: {}. Refusing to carry on \
+                     silently as if it had been saved.",
+                    hex::encode(intent_id),
+                    err
+                )
+            },
+        );
+        let encoded = engine_ids.to_vec().try_to_vec().unwrap_or_else(|err| {
+            panic!("Failed to encode pending engines for an intent: {}", err)
+        });
+        self.db
+            .put_cf(self.pending_engines_cf(), intent_id, encoded)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Failed to persist the pending engines for intent {}: \
+                     {}. Refusing to carry on silently as if it had been \
+                     saved.",
+                    hex::encode(intent_id),
+                    err
+                )
+            });
+    }
+
+    /// Record that `engine_id` has consumed a match for `intent_id`,
+    /// evicting the intent (and its pending-engines bookkeeping)
+    /// entirely once every engine it was handed to has done the same.
+    /// Returns whether the intent was evicted.
+    pub(crate) fn consume(
+        &self,
+        intent_id: &[u8],
+        engine_id: &EngineId,
+    ) -> bool {
+        let pending_cf = self.pending_engines_cf();
+        let remaining: Vec<EngineId> = self
+            .db
+            .get_cf(pending_cf, intent_id)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Failed to read the pending engines for intent {}: {}",
+                    hex::encode(intent_id),
+                    err
+                )
+            })
+            .map(|bytes| {
+                Vec::<EngineId>::try_from_slice(&bytes).unwrap_or_else(
+                    |err| {
+                        panic!(
+                            "Failed to decode the pending engines for \
+                             intent {}: {}",
+                            hex::encode(intent_id),
+                            err
+                        )
+                    },
+                )
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|id| id != engine_id)
+            .collect();
+
+        if remaining.is_empty() {
+            self.remove(intent_id);
+            true
+        } else {
+            let encoded = remaining.try_to_vec().unwrap_or_else(|err| {
+                panic!(
+                    "Failed to encode pending engines for an intent: {}",
+                    err
+                )
+            });
+            self.db.put_cf(pending_cf, intent_id, encoded).unwrap_or_else(
+                |err| {
+                    panic!(
+                        "Failed to persist the pending engines for intent \
+                         {}: {}",
+                        hex::encode(intent_id),
+                        err
+                    )
+                },
+            );
+            false
+        }
+    }
+
+    /// Resolve `engine_id` out of every intent's pending-engines set, as
+    /// if it had consumed every match it was ever handed, evicting any
+    /// intent whose set becomes empty as a result.
+    ///
+    /// Called when an engine is removed from the broker at runtime: an
+    /// intent fanned out to a since-removed engine can otherwise never be
+    /// evicted, since [`Self::consume`] would wait forever for a
+    /// check-in from an engine that no longer exists, leaving it
+    /// replayed into every engine still registered on every future
+    /// restart.
+    pub(crate) fn remove_engine(&self, engine_id: &EngineId) {
+        let pending_cf = self.pending_engines_cf();
+        let entries: Vec<(Vec<u8>, Vec<EngineId>)> = self
+            .db
+            .iterator_cf(pending_cf, IteratorMode::Start)
+            .map(|item| {
+                let (key, value) = item.unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to read the matchmaker's pending-engines \
+                         store: {}",
+                        err
+                    )
+                });
+                let pending =
+                    Vec::<EngineId>::try_from_slice(&value).unwrap_or_else(
+                        |err| {
+                            panic!(
+                                "Failed to decode the pending engines for \
+                                 an intent: {}",
+                                err
+                            )
+                        },
+                    );
+                (key.to_vec(), pending)
+            })
+            .collect();
+
+        for (intent_id, pending) in entries {
+            if !pending.iter().any(|id| id == engine_id) {
+                continue;
+            }
+            let remaining: Vec<EngineId> = pending
+                .into_iter()
+                .filter(|id| id != engine_id)
+                .collect();
+            if remaining.is_empty() {
+                self.remove(&intent_id);
+            } else {
+                let encoded = remaining.try_to_vec().unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to encode pending engines for an intent: {}",
+                        err
+                    )
+                });
+                self.db
+                    .put_cf(pending_cf, &intent_id, encoded)
+                    .unwrap_or_else(|err| {
+                        panic!(
+                            "Failed to persist the pending engines for \
+                             intent {}: {}",
+                            hex::encode(&intent_id),
+                            err
+                        )
+                    });
+            }
+        }
+    }
+
+    /// Forget an intent, along with its pending-engines bookkeeping,
+    /// unconditionally (e.g. on an operator-issued drop, or a peer mesh
+    /// "consumed" notification).
+    pub(crate) fn remove(&self, intent_id: &[u8]) {
+        self.db.delete_cf(self.cf(), intent_id).unwrap_or_else(|err| {
+            panic!(
+                "Failed to evict matched intent {} from the intent store: \
+                 {}",
+                hex::encode(intent_id),
+                err
+            )
+        });
+        self.db
+            .delete_cf(self.pending_engines_cf(), intent_id)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Failed to evict the pending engines for intent {} \
+                     from the intent store: {}",
+                    hex::encode(intent_id),
+                    err
+                )
+            });
+    }
+
+    /// Whether `intent_id` is currently persisted.
+    pub(crate) fn contains(&self, intent_id: &[u8]) -> bool {
+        self.db
+            .get_cf(self.cf(), intent_id)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Failed to read the matchmaker's intent store: {}",
+                    err
+                )
+            })
+            .is_some()
+    }
+
+    /// IDs of every intent currently persisted.
+    pub(crate) fn ids(&self) -> Vec<Vec<u8>> {
+        self.iter().map(|(id, _)| id).collect()
+    }
+
+    /// Number of intents currently persisted.
+    pub(crate) fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Every persisted `(intent_id, intent_data)` pair, e.g. to replay them
+    /// back into the dylib on startup.
+    pub(crate) fn iter(
+        &self,
+    ) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.db.iterator_cf(self.cf(), IteratorMode::Start).map(|item| {
+            let (key, value) = item.unwrap_or_else(|err| {
+                panic!(
+                    "Failed to read the matchmaker's intent store: {}",
+                    err
+                )
+            });
+            (key.to_vec(), value.to_vec())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+
+    /// A throwaway `IntentStore` backed by a uniquely-named directory
+    /// under the system temp dir, cleaned up on drop.
+    struct TempStore {
+        path: std::path::PathBuf,
+        store: IntentStore,
+    }
+
+    impl TempStore {
+        fn new() -> Self {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos();
+            let path = std::env::temp_dir()
+                .join(format!("matchmaker-intent-store-test-{}", nanos));
+            let store = IntentStore::open(&path);
+            Self { path, store }
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+
+    #[test]
+    fn evicts_only_once_every_engine_has_consumed() {
+        let db = TempStore::new();
+        let engine_a = EngineId("a".to_string());
+        let engine_b = EngineId("b".to_string());
+        db.store.put(b"intent", b"data", &[
+            engine_a.clone(),
+            engine_b.clone(),
+        ]);
+
+        assert!(!db.store.consume(b"intent", &engine_a));
+        assert!(db.store.contains(b"intent"));
+
+        assert!(db.store.consume(b"intent", &engine_b));
+        assert!(!db.store.contains(b"intent"));
+    }
+
+    #[test]
+    fn consume_by_an_unrelated_engine_does_not_evict() {
+        let db = TempStore::new();
+        let engine_a = EngineId("a".to_string());
+        let engine_b = EngineId("b".to_string());
+        db.store.put(b"intent", b"data", &[engine_a.clone()]);
+
+        assert!(!db.store.consume(b"intent", &engine_b));
+        assert!(db.store.contains(b"intent"));
+    }
+
+    #[test]
+    fn remove_engine_evicts_intents_pending_solely_against_it() {
+        let db = TempStore::new();
+        let engine_a = EngineId("a".to_string());
+        let engine_b = EngineId("b".to_string());
+        db.store.put(b"solo", b"data", &[engine_a.clone()]);
+        db.store.put(b"shared", b"data", &[
+            engine_a.clone(),
+            engine_b.clone(),
+        ]);
+
+        db.store.remove_engine(&engine_a);
+
+        assert!(!db.store.contains(b"solo"));
+        assert!(db.store.contains(b"shared"));
+
+        // The remaining engine can still consume its own match normally.
+        assert!(db.store.consume(b"shared", &engine_b));
+        assert!(!db.store.contains(b"shared"));
+    }
+}
@@ -0,0 +1,124 @@
+//! A bounded retry queue for transactions crafted from matched intents.
+//! Without it, a transient ledger outage or a wrapper epoch that expired
+//! between crafting and broadcasting would silently lose a matched trade
+//! the moment `broadcast_tx` failed once. Failed submissions are
+//! re-queued with an attempt counter and retried after a capped
+//! exponential backoff; the epoch is always re-queried on the next
+//! attempt rather than reused, since a stale one would likely just fail
+//! again.
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use super::broker::EngineId;
+
+/// How many failed submissions may be queued for retry at once. Once
+/// full, further failures are abandoned immediately instead of queued,
+/// the same way they would have been dropped outright without this
+/// queue.
+pub(crate) const MAX_QUEUE_DEPTH: usize = 256;
+
+/// How many attempts (including the first) to make for a single match
+/// before giving up on it.
+pub(crate) const MAX_ATTEMPTS: u32 = 8;
+
+/// A transaction submission waiting to be retried.
+pub(crate) struct PendingSubmission {
+    /// The engine whose match this transaction came from, kept around
+    /// only for logging.
+    pub(crate) engine_id: EngineId,
+    /// The Borsh-encoded `MatchedExchanges` this transaction is built
+    /// from. Re-parsed on every attempt rather than holding a half-built
+    /// `WrapperTx`, since the wrapper has to be rebuilt from scratch
+    /// anyway to carry a freshly queried epoch.
+    pub(crate) tx_data: Vec<u8>,
+    /// How many attempts have already been made, including the first one
+    /// that got this submission queued.
+    pub(crate) attempt: u32,
+    /// When this submission is next due for a retry.
+    pub(crate) ready_at: tokio::time::Instant,
+}
+
+// Ordered solely by `ready_at`, and reversed so that a `BinaryHeap` (a
+// max-heap) pops the earliest-due submission first, instead of the
+// latest. Without this, a `BinaryHeap` would sort the queue by
+// insertion-irrelevant default field order and a long-backed-off entry
+// could sit ahead of one that's due far sooner.
+impl PartialEq for PendingSubmission {
+    fn eq(&self, other: &Self) -> bool {
+        self.ready_at == other.ready_at
+    }
+}
+
+impl Eq for PendingSubmission {}
+
+impl PartialOrd for PendingSubmission {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingSubmission {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.ready_at.cmp(&self.ready_at)
+    }
+}
+
+/// The backoff to wait before the attempt numbered `attempt` (0-indexed,
+/// so `attempt == 0` is the delay before the first retry): 1s, 2s, 4s,
+/// ... capped at 60s.
+pub(crate) fn backoff_for(attempt: u32) -> Duration {
+    let secs = 1u64.checked_shl(attempt.min(6)).unwrap_or(u64::MAX);
+    Duration::from_secs(secs).min(Duration::from_secs(60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_up_to_the_cap() {
+        assert_eq!(backoff_for(0), Duration::from_secs(1));
+        assert_eq!(backoff_for(1), Duration::from_secs(2));
+        assert_eq!(backoff_for(2), Duration::from_secs(4));
+        assert_eq!(backoff_for(5), Duration::from_secs(32));
+    }
+
+    #[test]
+    fn caps_at_60_seconds_and_never_overflows() {
+        assert_eq!(backoff_for(6), Duration::from_secs(60));
+        assert_eq!(backoff_for(7), Duration::from_secs(60));
+        assert_eq!(backoff_for(u32::MAX), Duration::from_secs(60));
+    }
+
+    fn pending_due_in(
+        engine: &str,
+        secs_from_now: u64,
+    ) -> PendingSubmission {
+        PendingSubmission {
+            engine_id: EngineId(engine.to_string()),
+            tx_data: Vec::new(),
+            attempt: 0,
+            ready_at: tokio::time::Instant::now()
+                + Duration::from_secs(secs_from_now),
+        }
+    }
+
+    #[test]
+    fn a_binary_heap_pops_the_earliest_ready_at_first() {
+        let mut queue = std::collections::BinaryHeap::new();
+        // Pushed out of order, and with the farthest-out deadline first,
+        // the way an old, heavily backed-off entry could otherwise sit
+        // ahead of a freshly-queued one in a plain FIFO.
+        queue.push(pending_due_in("old", 60));
+        queue.push(pending_due_in("new", 1));
+        queue.push(pending_due_in("middle", 10));
+
+        assert_eq!(queue.pop().unwrap().engine_id, EngineId("new".into()));
+        assert_eq!(
+            queue.pop().unwrap().engine_id,
+            EngineId("middle".into())
+        );
+        assert_eq!(queue.pop().unwrap().engine_id, EngineId("old".into()));
+    }
+}
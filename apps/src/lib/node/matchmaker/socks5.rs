@@ -0,0 +1,186 @@
+//! A minimal SOCKS5 client, just enough to route the matchmaker's outbound
+//! connections (to the intent gossiper and to the ledger) through a local
+//! proxy such as a Tor daemon, so the matchmaker's own network location is
+//! never revealed to the nodes it talks to.
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+/// Open a TCP connection to `proxy_addr` and ask it to `CONNECT` to
+/// `dest_host:dest_port` on our behalf, returning the established stream.
+///
+/// `dest_host` is sent to the proxy as a domain name (SOCKS5 address type
+/// `0x03`) rather than a pre-resolved IP, so that DNS resolution happens on
+/// the proxy side (e.g. inside Tor) instead of leaking from the matchmaker.
+pub fn connect(
+    proxy_addr: SocketAddr,
+    dest_host: &str,
+    dest_port: u16,
+) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect(proxy_addr)?;
+    handshake(stream, dest_host, dest_port)
+}
+
+/// Like [`connect`], but bounded by `timeout` for both the initial dial to
+/// `proxy_addr` and every read during the handshake, so a proxy that's up
+/// but stalls mid-handshake can't block the caller forever the way a plain
+/// blocking [`connect`] would. Meant for liveness probes, which need a
+/// bounded worst case more than they need the connection itself.
+pub fn connect_timeout(
+    proxy_addr: SocketAddr,
+    dest_host: &str,
+    dest_port: u16,
+    timeout: Duration,
+) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect_timeout(&proxy_addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    handshake(stream, dest_host, dest_port)
+}
+
+/// Run the SOCKS5 greeting and CONNECT exchange over an already-dialed
+/// `stream`, returning it once the proxy has confirmed the connection.
+fn handshake(
+    mut stream: TcpStream,
+    dest_host: &str,
+    dest_port: u16,
+) -> io::Result<TcpStream> {
+    if dest_host.len() > u8::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "destination hostname is too long for a SOCKS5 request",
+        ));
+    }
+
+    // Greeting: protocol version 5, offering a single "no authentication"
+    // method (0x00).
+    stream.write_all(&[0x05, 0x01, 0x00])?;
+    let mut method_reply = [0u8; 2];
+    stream.read_exact(&mut method_reply)?;
+    if method_reply[0] != 0x05 || method_reply[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "SOCKS5 proxy did not accept the no-auth method",
+        ));
+    }
+
+    // CONNECT request, addressed by domain name.
+    let mut request = vec![0x05, 0x01, 0x00, 0x03, dest_host.len() as u8];
+    request.extend_from_slice(dest_host.as_bytes());
+    request.extend_from_slice(&dest_port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply_head = [0u8; 4];
+    stream.read_exact(&mut reply_head)?;
+    if reply_head[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "SOCKS5 CONNECT was refused by the proxy (reply code {})",
+                reply_head[1]
+            ),
+        ));
+    }
+
+    // The reply carries the proxy's bound address, which we don't need but
+    // still have to drain from the stream.
+    let bound_addr_len = match reply_head[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len)?;
+            len[0] as usize
+        }
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("SOCKS5 proxy replied with an unknown address type {}", other),
+            ))
+        }
+    };
+    let mut discarded = vec![0u8; bound_addr_len + 2];
+    stream.read_exact(&mut discarded)?;
+
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    /// Accept one connection on `listener`, read the greeting and CONNECT
+    /// request exactly as a real SOCKS5 proxy would, and reply with
+    /// `reply_code`. Asserts the CONNECT request's address type here, and
+    /// returns its host length and port, so the caller can assert the
+    /// rest of the request was framed correctly.
+    fn run_fake_proxy(
+        listener: TcpListener,
+        reply_code: u8,
+    ) -> (u8, u16) {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let mut greeting = [0u8; 3];
+        stream.read_exact(&mut greeting).unwrap();
+        assert_eq!(greeting, [0x05, 0x01, 0x00]);
+        stream.write_all(&[0x05, 0x00]).unwrap();
+
+        let mut request_head = [0u8; 5];
+        stream.read_exact(&mut request_head).unwrap();
+        assert_eq!(&request_head[..4], [0x05, 0x01, 0x00, 0x03]);
+        let host_len = request_head[4] as usize;
+        let mut host = vec![0u8; host_len];
+        stream.read_exact(&mut host).unwrap();
+        let mut port_buf = [0u8; 2];
+        stream.read_exact(&mut port_buf).unwrap();
+        let port = u16::from_be_bytes(port_buf);
+
+        // Bound address: IPv4, 0.0.0.0:0.
+        stream
+            .write_all(&[0x05, reply_code, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .unwrap();
+
+        (host_len as u8, port)
+    }
+
+    #[test]
+    fn sends_a_well_formed_connect_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_addr = listener.local_addr().unwrap();
+        let handle =
+            std::thread::spawn(move || run_fake_proxy(listener, 0x00));
+
+        let stream = connect(proxy_addr, "example.com", 1234).unwrap();
+        drop(stream);
+
+        let (host_len, port) = handle.join().unwrap();
+        assert_eq!(host_len, "example.com".len() as u8);
+        assert_eq!(port, 1234);
+    }
+
+    #[test]
+    fn surfaces_a_refused_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_addr = listener.local_addr().unwrap();
+        let handle =
+            std::thread::spawn(move || run_fake_proxy(listener, 0x05));
+
+        let result = connect(proxy_addr, "example.com", 1234);
+        assert!(result.is_err());
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn rejects_a_hostname_too_long_to_frame() {
+        let too_long = "a".repeat(u8::MAX as usize + 1);
+        let result = connect(
+            "127.0.0.1:1".parse().unwrap(),
+            &too_long,
+            1234,
+        );
+        assert!(result.is_err());
+    }
+}
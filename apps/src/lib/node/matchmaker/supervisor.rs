@@ -0,0 +1,152 @@
+//! Periodic liveness checks for the matchmaker's gossiper and ledger
+//! connections, with the outcome surfaced through the admin RPC so an
+//! operator can tell whether the matchmaker is actually able to match
+//! intents right now, rather than having silently stopped after a restart
+//! on the other end.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(not(feature = "ABCI"))]
+use tendermint_config::net::Address as TendermintAddress;
+#[cfg(feature = "ABCI")]
+use tendermint_config_abci::net::Address as TendermintAddress;
+
+use super::socks5;
+use crate::cli::args;
+use crate::client::rpc;
+
+/// Connectivity of one of the matchmaker's external connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum ConnectivityState {
+    /// The connection was probed successfully most recently.
+    Connected,
+    /// The connection just dropped and a reconnect attempt is in flight.
+    Reconnecting,
+    /// The connection is down and the supervisor is backing off before the
+    /// next attempt.
+    Down,
+}
+
+/// A point-in-time snapshot of the matchmaker's connectivity, returned by
+/// the admin RPC's `Stats` request.
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct ConnectivityReport {
+    /// Connectivity to the intent gossiper node.
+    pub gossiper: ConnectivityState,
+    /// Connectivity to the ledger.
+    pub ledger: ConnectivityState,
+}
+
+impl Default for ConnectivityReport {
+    fn default() -> Self {
+        Self {
+            gossiper: ConnectivityState::Down,
+            ledger: ConnectivityState::Down,
+        }
+    }
+}
+
+/// Shared, admin-RPC-visible connectivity state.
+pub(crate) type SharedConnectivity = Arc<Mutex<ConnectivityReport>>;
+
+/// Spawn a supervisor thread that probes the gossiper and ledger every
+/// `interval`, refreshing `connectivity` with the outcome. Repeated
+/// gossiper probe failures back off exponentially (capped at 60s) between
+/// attempts.
+///
+/// This probe runs alongside, not instead of, [`super::Runner::listen`]'s
+/// own reconnect loop: the probe's job is to make connectivity observable,
+/// while tearing down and re-establishing the actual gossip connection is
+/// driven by `listen` noticing the live connection error out.
+pub(crate) fn spawn_health_check(
+    gossiper_addr: SocketAddr,
+    proxy: Option<SocketAddr>,
+    ledger_address: TendermintAddress,
+    interval: Duration,
+    connectivity: SharedConnectivity,
+) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let mut backoff = interval;
+        loop {
+            let gossiper_ok = ping_gossiper(gossiper_addr, proxy);
+            let ledger_ok =
+                rt.block_on(ping_ledger(ledger_address.clone(), proxy));
+
+            {
+                let mut report = connectivity.lock().unwrap();
+                report.gossiper = if gossiper_ok {
+                    backoff = interval;
+                    ConnectivityState::Connected
+                } else if report.gossiper == ConnectivityState::Connected {
+                    ConnectivityState::Reconnecting
+                } else {
+                    backoff = (backoff * 2).min(Duration::from_secs(60));
+                    ConnectivityState::Down
+                };
+                report.ledger = if ledger_ok {
+                    ConnectivityState::Connected
+                } else {
+                    ConnectivityState::Down
+                };
+            }
+
+            std::thread::sleep(if gossiper_ok { interval } else { backoff });
+        }
+    });
+}
+
+/// A lightweight liveness probe for the gossiper: open (and immediately
+/// drop) a connection to it, through the SOCKS5 proxy if one is configured.
+/// This is independent of the matchmaker's real, long-lived gossip
+/// connection, which `Runner::listen` owns.
+fn ping_gossiper(
+    gossiper_addr: SocketAddr,
+    proxy: Option<SocketAddr>,
+) -> bool {
+    let probe = match proxy {
+        Some(proxy_addr) => socks5::connect_timeout(
+            proxy_addr,
+            &gossiper_addr.ip().to_string(),
+            gossiper_addr.port(),
+            Duration::from_secs(5),
+        )
+        .map(|_| ()),
+        None => std::net::TcpStream::connect_timeout(
+            &gossiper_addr,
+            Duration::from_secs(5),
+        )
+        .map(|_| ()),
+    };
+    probe.is_ok()
+}
+
+/// A liveness probe for the ledger: a cheap, read-only RPC query, bounded
+/// by a timeout so an unresponsive ledger is reported as down rather than
+/// hanging the health check loop. A transport or decode error is treated
+/// the same as a timeout: both just mean "not reachable right now".
+///
+/// `rpc::query_epoch` does its I/O on a blocking socket without ever
+/// yielding, so it runs on the blocking pool and the timeout bounds the
+/// wait for it here; a stalled query's thread stays blocked until its
+/// socket gives up.
+async fn ping_ledger(
+    ledger_address: TendermintAddress,
+    proxy: Option<SocketAddr>,
+) -> bool {
+    let handle = tokio::runtime::Handle::current();
+    let query = tokio::task::spawn_blocking(move || {
+        handle.block_on(rpc::query_epoch(args::Query { ledger_address }, proxy))
+    });
+    tokio::time::timeout(Duration::from_secs(5), query)
+        .await
+        .map(|joined| matches!(joined, Ok(Ok(_))))
+        .unwrap_or(false)
+}
@@ -0,0 +1,16 @@
+//! Loads WASM bytecode (transactions, validity predicates) from the
+//! configured WASM directory.
+
+use std::path::Path;
+
+/// Read the WASM bytecode at `path`, relative to `wasm_dir`.
+pub fn read_wasm(wasm_dir: impl AsRef<Path>, path: impl AsRef<Path>) -> Vec<u8> {
+    let full_path = wasm_dir.as_ref().join(path.as_ref());
+    std::fs::read(&full_path).unwrap_or_else(|err| {
+        panic!(
+            "Failed to read wasm code from {}: {}",
+            full_path.to_string_lossy(),
+            err
+        )
+    })
+}